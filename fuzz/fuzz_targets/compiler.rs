@@ -0,0 +1,14 @@
+#![no_main]
+//Compiler and assembler source for the TMS1000 and TMS1100
+
+use libfuzzer_sys::fuzz_target;
+use tms::assembler;
+use tms::compiler;
+use tms::variant::ChipVariant;
+
+fuzz_target!(|data: &str| {
+    for version in [ChipVariant::TMS1000, ChipVariant::TMS1100] {
+        let _ = compiler::compile(data.to_string(), version);
+        let _ = assembler::assemble(data.to_string(), version);
+    }
+});
@@ -0,0 +1,43 @@
+#![no_main]
+//Arbitrary ROM, instruction PLA and K inputs on every variant and power-on policy
+
+use std::collections::HashMap;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use tms::TMS1000::{PowerOnPolicy, SYSTEM};
+use tms::variant::ChipVariant;
+
+const VARIANTS : [ChipVariant; 6] = [ChipVariant::TMS1000, ChipVariant::TMS1070, ChipVariant::TMS1100, ChipVariant::TMS1200, ChipVariant::TMS1270, ChipVariant::TMS1300];
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    variant: u8,
+    policy: u8,
+    seed: u64,
+    init: u32, //held from halfway through, if nonzero
+    ipla: HashMap<u8, u32>,
+    k: Vec<u8>,
+    rom: Vec<u8>,
+}
+
+fuzz_target!(|input: Input| {
+    let policy = match input.policy % 3 {
+        0 => PowerOnPolicy::Zero,
+        1 => PowerOnPolicy::Sentinel,
+        _ => PowerOnPolicy::Random(input.seed),
+    };
+    let ipla: HashMap<u32, u32> = input.ipla.iter().map(|(&k, &v)| (k as u32, v)).collect();
+    let mut system = match SYSTEM::from_tables(VARIANTS[input.variant as usize % VARIANTS.len()], input.rom, ipla, HashMap::new(), policy) {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+    system.set_logging();
+    system.set_definedness_tracking(true);
+    for (cycle, &k) in input.k.iter().take(4096).enumerate() {
+        if cycle == 2048 && input.init > 0 {
+            system.set_init(input.init);
+        }
+        system.instruction_cycle_mut(k % 16);
+    }
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tms::TMS1000::SYSTEM;
+
+fuzz_target!(|data: &str| {
+    let _ = SYSTEM::parse_PLA(data);
+});
@@ -2,16 +2,21 @@
 #![allow(non_snake_case)]
 #![allow(unused_parens)]
 #![allow(non_upper_case_globals)]
+#![allow(clippy::needless_return)]
 //using all caps to denote actual system variables
 //and camelcase to denote handler elements
 
 use std::fs;
 use std::io::Read;
 use std::collections::HashMap;
+use std::str::FromStr;
 use regex::Regex;
 
 
+use crate::coverage::Coverage;
+use crate::profiler::Profile;
 use crate::decompiler;
+use crate::variant::ChipVariant;
 
 //HELPER FUNCTIONS
 //imitates smaller than u8
@@ -50,15 +55,138 @@ fn reversebits_u2(value : u8) -> u8 {
     return value.reverse_bits() >> 6;
 }
 
-static PC_SEQ: [usize; 64] = [0x00, 0x01, 0x03, 0x07, 0x0F, 0x1F, 0x3F, 0x3E, 0x3D, 0x3B, 0x37, 0x2F, 0x1E, 0x3C, 0x39, 0x33, 0x27, 0x0E, 0x1D, 0x3A, 0x35, 0x2B, 0x16, 0x2C, 0x18, 0x30, 0x21, 0x02, 0x05, 0x0B, 0x17, 0x2E, 0x1C, 0x38, 0x31, 0x23, 0x06, 0x0D, 0x1B, 0x36, 0x2D, 0x1A, 0x34, 0x29, 0x12, 0x24, 0x08, 0x11, 0x22, 0x04, 0x09, 0x13, 0x26, 0x0C, 0x19, 0x32, 0x25, 0x0A, 0x15, 0x2A, 0x14, 0x28, 0x10, 0x20];
+pub static PC_SEQ: [usize; 64] = [0x00, 0x01, 0x03, 0x07, 0x0F, 0x1F, 0x3F, 0x3E, 0x3D, 0x3B, 0x37, 0x2F, 0x1E, 0x3C, 0x39, 0x33, 0x27, 0x0E, 0x1D, 0x3A, 0x35, 0x2B, 0x16, 0x2C, 0x18, 0x30, 0x21, 0x02, 0x05, 0x0B, 0x17, 0x2E, 0x1C, 0x38, 0x31, 0x23, 0x06, 0x0D, 0x1B, 0x36, 0x2D, 0x1A, 0x34, 0x29, 0x12, 0x24, 0x08, 0x11, 0x22, 0x04, 0x09, 0x13, 0x26, 0x0C, 0x19, 0x32, 0x25, 0x0A, 0x15, 0x2A, 0x14, 0x28, 0x10, 0x20];
+
+//Execution order index of a PC, the inverse of PC_SEQ
+pub fn exec_index(pc : usize) -> usize {
+    return PC_SEQ.iter().position(|&i| i == pc).unwrap_or(0); //PC_SEQ holds every 6-bit value
+}
+
+//A ROM index as "chapter page pc", the address format of the compiler
+pub fn format_address(index : usize) -> String {
+    return format!("{} {:0>2} {:0>2}", index / 1024, (index / 64) % 16, index % 64);
+}
+
+//splitmix64, used to generate reproducible power-on garbage
+fn next_random(state : &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    return z ^ (z >> 31);
+}
 
 //Main body
 
+//Contents of RAM, the accumulator, X, Y and the status latch at power-on
+//Real chips power up with garbage in all of them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerOnPolicy {
+    Zero, //Everything cleared to 0
+    Sentinel, //Everything set to the out of range value 255, so stray reads are obvious in the debugger
+    Random(u64), //Random values within each element's range, reproducible from the seed
+}
+
+impl FromStr for PowerOnPolicy {
+    type Err = String;
+
+    //"zero", "sentinel" or "random:<seed>"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        match lower.as_str() {
+            "zero" => return Ok(PowerOnPolicy::Zero),
+            "sentinel" => return Ok(PowerOnPolicy::Sentinel),
+            _ => (),
+        }
+        if let Some(seed) = lower.strip_prefix("random:") {
+            if let Ok(v) = seed.parse::<u64>() {
+                return Ok(PowerOnPolicy::Random(v));
+            }
+        }
+        return Err(format!("Unknown power-on policy \"{}\" (expected zero, sentinel or random:<seed>)", s));
+    }
+}
+
+//What happens when an instruction addresses RAM or ROM the chip does not have
+//X and Y only leave their hardware range through the sentinel power-on policy or a core bug; ROM
+//images are fitted to the chip when loaded, so ROM addresses only through a core bug
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessPolicy {
+    Panic, //Abort, for catching core bugs in tests
+    Wrap, //Drop the address bits the chip has no lines for, as the hardware does
+    Halt, //Record an AccessFault and stop executing until the chip is reinitialized
+}
+
+impl FromStr for AccessPolicy {
+    type Err = String;
+
+    //"panic", "wrap" or "halt"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s.trim().to_lowercase().as_str() {
+            "panic" => Ok(AccessPolicy::Panic),
+            "wrap" => Ok(AccessPolicy::Wrap),
+            "halt" => Ok(AccessPolicy::Halt),
+            _ => Err(format!("Unknown access policy \"{}\" (expected panic, wrap or halt)", s)),
+        };
+    }
+}
+
+//Shadow of every storage element that can power up holding garbage
+//true once the element has been written with a defined value
+#[derive(Clone)]
+#[allow(clippy::upper_case_acronyms)] //all caps marks system state, as with SYSTEM
+struct DEFINEDNESS {
+    RAM_ARRAY: [[bool; 16]; 8],
+    ACCUMULATOR: bool,
+    X_REGISTER: bool,
+    Y_REGISTER: bool,
+    STATUS_LATCH: bool,
+    P_MUX: bool, //Adder inputs inherit the definedness of their source
+    N_MUX: bool,
+}
+
+impl DEFINEDNESS {
+    fn power_on() -> Self {
+        return DEFINEDNESS {
+            RAM_ARRAY: [[false; 16]; 8],
+            ACCUMULATOR: false,
+            X_REGISTER: false,
+            Y_REGISTER: false,
+            STATUS_LATCH: false,
+            P_MUX: true,
+            N_MUX: true,
+        };
+    }
+}
+
+//A read of RAM or a register that had never been written, found by definedness tracking
+#[derive(Clone, Debug, PartialEq)]
+pub struct UndefinedRead {
+    pub rom_address: usize, //index into the ROM of the instruction that did the read
+    pub chapter: usize,
+    pub page: u8,
+    pub pc: usize,
+    pub instruction: u8,
+    pub source: String, //what was read, and by which microinstruction
+}
+
+//An out of range RAM or ROM access that halted the system under AccessPolicy::Halt
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessFault {
+    pub rom_address: usize, //index into the ROM of the instruction that made the access
+    pub chapter: usize,
+    pub page: u8,
+    pub pc: usize,
+    pub instruction: u8,
+    pub access: String, //what was addressed, and the limit it broke
+}
+
 #[derive(Clone)]
 struct SYSTEM_STATE {
     LOG: Vec<String>,
 
     INSTRUCTION: u8, //u8 current instruction
+    INSTRUCTION_ADDRESS: (usize, u8, usize), //chapter, page and PC the current instruction was loaded from
     INSTRUCTION_DECODED: u32,
     STEP: usize,
 
@@ -98,18 +226,28 @@ struct SYSTEM_STATE {
     O_OUTPUT: u32, //U5, O output register. Used to transmit data
 
     K_INPUT: u8, //K input registers, K1, K2, K4, and K8
+    DEFINED: DEFINEDNESS,
+    INIT_PIN: u32, //Steps left to hold the chip in reset; nonzero while the INIT pin is held high
+    HALTED: bool, //Set by an access fault under AccessPolicy::Halt; cleared by INIT
     //In order to maintain persistence across instruction cycles, like an analog button press would, I thought of giving it a lifespan variable (like Status)
     //But decided that's more germane to the physical layer
 }
 
 #[derive(Clone)]
 pub struct SYSTEM {
-    VERSION: u32,
+    VERSION: ChipVariant,
     STATE: SYSTEM_STATE,
-    ROM_ARRAY: Vec<u8>,
+    ROM_ARRAY: Vec<u8>, //always the variant's ROM size, see ChipVariant::fit_rom
+    ROM_WARNING: Option<String>, //set if the image had to be mirrored, padded or cut to fit
     INSTRUCTION_PLA: HashMap<u32, u32>,
     OUTPUT_PLA: HashMap<u32, u32>,
     logging: bool, //If expanded, should be a general 'systems settings' object
+    definedness: bool, //Definedness tracking of RAM and registers
+    UNDEFINED_READS: Vec<UndefinedRead>,
+    ACCESS_POLICY: AccessPolicy,
+    ACCESS_FAULTS: Vec<AccessFault>,
+    COVERAGE: Option<Coverage>, //None unless coverage is being recorded
+    PROFILE: Option<Profile>, //None unless profiling
 }
 
 impl SYSTEM {
@@ -123,8 +261,8 @@ impl SYSTEM {
                             return reversebits_u4(self.STATE.INSTRUCTION);}, //constant
             0x08..=0x0F => {self.log_append(format!("CKI: Returning K inputs {}", self.STATE.K_INPUT));
                             return self.STATE.K_INPUT;},
-            0x30..=0x3A => {self.log_append(format!("CKI: Returning bitmask {}", (15 - reversebits_u2(self.STATE.INSTRUCTION))));
-                            return 15 - reversebits_u2(self.STATE.INSTRUCTION);}, //bit mask
+            0x30..=0x3B => {self.log_append(format!("CKI: Returning bitmask {}", 15 ^ (1 << reversebits_u2(self.STATE.INSTRUCTION))));
+                            return 15 ^ (1 << reversebits_u2(self.STATE.INSTRUCTION));}, //bit mask, inverted: TBIT1 ORs it with RAM
             0x40..=0x7F => {self.log_append(format!("CKI: Returning constant operand {}", reversebits_u4(self.STATE.INSTRUCTION)));
                             return reversebits_u4(self.STATE.INSTRUCTION);},
             _ => {self.log_append("CKI: Returning 0".to_string());
@@ -132,10 +270,109 @@ impl SYSTEM {
         }
     }
 
-    fn ADDER(&mut self) -> (u8, u8) {
-        if (self.STATE.P_MUX == 255) || (self.STATE.N_MUX == 255) {
-            self.log_append("Adder: ALERT! Inputs set to uninitialized values".into());
+    //Definedness tracking
+    //Records a read of an element that has never been written
+    fn CHECK_DEFINED(&mut self, defined : bool, source : &str) {
+        if self.definedness && !defined {
+            let (chapter, page, pc) = self.STATE.INSTRUCTION_ADDRESS;
+            self.log_append(format!("{}: ALERT! Read of undefined value at ROM address {} {} {}", source, chapter, page, pc));
+            self.UNDEFINED_READS.push(UndefinedRead {
+                rom_address: (1024 * chapter) + (64 * page as usize) + pc,
+                chapter,
+                page,
+                pc,
+                instruction: self.STATE.INSTRUCTION,
+                source: source.to_string(),
+            });
+        }
+    }
+
+    //Memory access
+    //Applies the access policy to `address` in a memory of `size` elements
+    //Returns the element to use, or None if the access is abandoned because the system has halted
+    fn ACCESS(&mut self, address : usize, size : usize, what : &str) -> Option<usize> {
+        if address < size {
+            return Some(address);
+        }
+        if self.STATE.HALTED {
+            return None;
         }
+        let (chapter, page, pc) = self.STATE.INSTRUCTION_ADDRESS;
+        let access = format!("{} {} out of range ({} exist)", what, address, size);
+        match self.ACCESS_POLICY {
+            AccessPolicy::Wrap => {
+                if size == 0 {
+                    return None;
+                }
+                return Some(address % size);
+            },
+            AccessPolicy::Panic => panic!("{} at ROM address {} {} {}", access, chapter, page, pc),
+            AccessPolicy::Halt => {
+                self.log_append(format!("{}: ALERT! Halted at ROM address {} {} {}", access, chapter, page, pc));
+                self.ACCESS_FAULTS.push(AccessFault {
+                    rom_address: (1024 * chapter) + (64 * page as usize) + pc,
+                    chapter,
+                    page,
+                    pc,
+                    instruction: self.STATE.INSTRUCTION,
+                    access,
+                });
+                self.STATE.HALTED = true;
+                return None;
+            },
+        }
+    }
+
+    //X addresses the chip's RAM files and Y the 16 words in each
+    fn RAM_ADDRESS(&mut self) -> Option<(usize, usize)> {
+        let x = self.ACCESS(self.STATE.X_REGISTER, self.VERSION.ram_files(), "RAM file")?;
+        let y = self.ACCESS(self.STATE.Y_REGISTER, 16, "RAM word")?;
+        return Some((x, y));
+    }
+
+    //Abandoned reads return 0, and abandoned writes are dropped
+    fn RAM_READ(&mut self) -> u8 {
+        return match self.RAM_ADDRESS() {
+            Some((x, y)) => self.STATE.RAM_ARRAY[x][y],
+            None => 0,
+        };
+    }
+
+    fn RAM_WRITE(&mut self, value : u8) {
+        if let Some((x, y)) = self.RAM_ADDRESS() {
+            self.STATE.RAM_ARRAY[x][y] = value;
+        }
+    }
+
+    fn ROM_READ(&mut self, index : usize) -> u8 {
+        return match self.ACCESS(index, self.ROM_ARRAY.len(), "ROM address") {
+            Some(v) => self.ROM_ARRAY[v],
+            None => 0,
+        };
+    }
+
+    //Checks the RAM address registers, and the addressed RAM word if `read` is set
+    //Returns whether the addressed word holds a defined value
+    fn CHECK_RAM(&mut self, name : &str, read : bool) -> bool {
+        self.CHECK_DEFINED(self.STATE.DEFINED.X_REGISTER, &format!("{}: X register", name));
+        self.CHECK_DEFINED(self.STATE.DEFINED.Y_REGISTER, &format!("{}: Y register", name));
+        let defined = match self.RAM_ADDRESS() {
+            Some((x, y)) => self.STATE.DEFINED.RAM_ARRAY[x][y] && self.STATE.DEFINED.X_REGISTER && self.STATE.DEFINED.Y_REGISTER,
+            None => return false,
+        };
+        if read {
+            self.CHECK_DEFINED(defined, &format!("{}: RAM {}, {}", name, self.STATE.X_REGISTER, self.STATE.Y_REGISTER));
+        }
+        return defined;
+    }
+
+    fn SET_RAM_DEFINED(&mut self, defined : bool) {
+        if let Some((x, y)) = self.RAM_ADDRESS() {
+            self.STATE.DEFINED.RAM_ARRAY[x][y] = defined;
+        }
+    }
+
+    fn ADDER(&mut self) -> (u8, u8) {
         let value: u32 = self.STATE.P_MUX as u32 + self.STATE.N_MUX as u32 + self.STATE.ADDER_INC as u32;
         let carry = if value > 15 { 1 } else { 0 };
         let return_value = (carry, u4(value as u8));
@@ -163,8 +400,8 @@ impl SYSTEM {
     // }
 
     fn SET_PC(&mut self, value : usize) {
-        self.STATE.PROGRAM_COUNTER = value;
-        self.STATE.PC_INDEX = PC_SEQ.iter().position(|&i| i == value).unwrap(); //this should be guarenteed; thus the use of unwrap()
+        self.STATE.PROGRAM_COUNTER = u6_usize(value);
+        self.STATE.PC_INDEX = exec_index(self.STATE.PROGRAM_COUNTER);
         self.log_append(format!("Program Counter: set to {} ({})", self.STATE.PROGRAM_COUNTER, self.STATE.PC_INDEX));
         if self.STATE.CALL_LATCH == 0 {
             self.STATE.SUBROUTINE_RETURN = PC_SEQ[self.STATE.PC_INDEX];
@@ -268,17 +505,20 @@ impl SYSTEM {
     //Load X register with constant
     fn LDX_TMS1000(&mut self) {
         self.STATE.X_REGISTER = reversebits_u2(self.STATE.INSTRUCTION) as usize;
+        self.STATE.DEFINED.X_REGISTER = true;
         self.log_append(format!("LDX: X register set to {}", self.STATE.X_REGISTER));
     }
 
     fn LDX_TMS1100(&mut self) {
         self.STATE.X_REGISTER = reversebits_u3(self.STATE.INSTRUCTION) as usize;
+        self.STATE.DEFINED.X_REGISTER = true;
         self.log_append(format!("LDX: X register set to {}", self.STATE.X_REGISTER));
     }
 
     //Complement X
     fn COMX (&mut self) {
-        if (self.VERSION == 1100) || (self.VERSION == 1300) {
+        self.CHECK_DEFINED(self.STATE.DEFINED.X_REGISTER, "COMX: X register");
+        if self.VERSION.is_TMS1100() {
             //Changes MSB of X register
             self.STATE.X_REGISTER ^= 0b1 << 2;
             self.log_append(format!("COMX: X register set to {}", self.STATE.X_REGISTER));
@@ -293,10 +533,9 @@ impl SYSTEM {
     //Transfer data from accumulator and status latch to O outputs
     fn TDO (&mut self) {
         //Acc and SL transferred to O-output register
-        if (self.STATE.ACCUMULATOR == 255) || (self.STATE.STATUS_LATCH == 255) {
-            self.log_append("TDO: ALERT! Uninitialized value being stored.".into());
-        }
-        self.STATE.O_OUTPUT = u5_u32((self.STATE.ACCUMULATOR + (self.STATE.STATUS_LATCH << 4)).into());
+        self.CHECK_DEFINED(self.STATE.DEFINED.ACCUMULATOR, "TDO: Accumulator");
+        self.CHECK_DEFINED(self.STATE.DEFINED.STATUS_LATCH, "TDO: Status latch");
+        self.STATE.O_OUTPUT = u5_u32(self.STATE.ACCUMULATOR as u32 + ((self.STATE.STATUS_LATCH as u32) << 4));
         self.log_append(format!("TDO: O output set to {:b}", self.STATE.O_OUTPUT));
     }
 
@@ -316,9 +555,7 @@ impl SYSTEM {
     //Set R output addressed by Y
     fn SETR (&mut self) {
         //sets R(Y) to 1; if Y out of range, no-op
-        if self.STATE.Y_REGISTER == 255 {
-            self.log_append("SETR: ALERT! Uninitialized Y register value being used.".into());
-        }
+        self.CHECK_DEFINED(self.STATE.DEFINED.Y_REGISTER, "SETR: Y register");
         if (self.STATE.Y_REGISTER < self.STATE.R_OUTPUT.len()) && (self.STATE.X_REGISTER < 4) {
             self.STATE.R_OUTPUT[self.STATE.Y_REGISTER] = 1;
             self.log_append(format!("SETR: R output {} set to 1", self.STATE.Y_REGISTER));
@@ -331,9 +568,7 @@ impl SYSTEM {
     //Reset R output addressed by Y
     fn RSTR (&mut self) {
         //sets R(Y) to 0; if Y out of range, no-op
-        if self.STATE.Y_REGISTER == 255 {
-            self.log_append("SETR: ALERT! Uninitialized Y register value being used.".into());
-        }
+        self.CHECK_DEFINED(self.STATE.DEFINED.Y_REGISTER, "RSTR: Y register");
         if (self.STATE.Y_REGISTER < self.STATE.R_OUTPUT.len()) && (self.STATE.X_REGISTER < 4) {
             self.STATE.R_OUTPUT[self.STATE.Y_REGISTER] = 0;
             self.log_append(format!("RSETR: R output {} set to 0", self.STATE.Y_REGISTER));
@@ -346,10 +581,15 @@ impl SYSTEM {
     //Set memory bit
     fn SBIT (&mut self) {
         //sets BIT of RAM(X,Y) to 1
+        self.CHECK_RAM("SBIT", true);
         let BIT_U8 = reversebits_u2(self.STATE.INSTRUCTION);
-        let IS_SET = self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER] & (1_u8 << BIT_U8) != 0;
+        let WORD = self.RAM_READ();
+        if self.STATE.HALTED {
+            return;
+        }
+        let IS_SET = WORD & (1_u8 << BIT_U8) != 0;
         if !(IS_SET) {
-            self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER] = u4(self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER] + (1_u8 << BIT_U8));
+            self.RAM_WRITE(u4(WORD | (1_u8 << BIT_U8)));
             self.log_append(format!("SBIT: Set bit {} at RAM address {}, {} to 1", BIT_U8, self.STATE.X_REGISTER, self.STATE.Y_REGISTER));
         }
         else {
@@ -360,10 +600,15 @@ impl SYSTEM {
     //Reset memory bit
     fn RBIT (&mut self) {
         //sets BIT of RAM(X,Y) to 0
+        self.CHECK_RAM("RBIT", true);
         let BIT_U8 = reversebits_u2(self.STATE.INSTRUCTION);
-        let IS_SET = self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER] & (1_u8 << BIT_U8) != 0;
+        let WORD = self.RAM_READ();
+        if self.STATE.HALTED {
+            return;
+        }
+        let IS_SET = WORD & (1_u8 << BIT_U8) != 0;
         if (IS_SET) {
-            self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER] = u4(self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER] - (1_u8 << BIT_U8));
+            self.RAM_WRITE(u4(WORD & !(1_u8 << BIT_U8)));
             self.log_append(format!("RBIT: Set bit {} at RAM address {}, {} to 0", BIT_U8, self.STATE.X_REGISTER, self.STATE.Y_REGISTER));
         }
         else {
@@ -372,23 +617,28 @@ impl SYSTEM {
     }
 
     //P-MUX instructions
+    //Sources selected together are ORed onto the adder input, as on the chip (TBIT1 relies on CKP + MTP)
 
     //CKI to P-adder input
     fn CKP(&mut self) {
         self.log_append("CKP: P-MUX set to output CKI".to_string());
-        self.STATE.P_MUX = self.CKI();
+        self.STATE.P_MUX |= self.CKI();
     }
 
     //Y-register to P-adder input
     fn YTP(&mut self) {
         self.log_append(format!("YTP: P-MUX set to output Y register value {}", self.STATE.Y_REGISTER));
-        self.STATE.P_MUX = self.STATE.Y_REGISTER as u8;
+        self.CHECK_DEFINED(self.STATE.DEFINED.Y_REGISTER, "YTP: Y register");
+        self.STATE.P_MUX |= self.STATE.Y_REGISTER as u8;
+        self.STATE.DEFINED.P_MUX &= self.STATE.DEFINED.Y_REGISTER;
     }
 
     //Memory (X, Y) to P-adder input
     fn MTP(&mut self) {
-        self.STATE.P_MUX = self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER];
-        self.log_append(format!("MTP: P-MUX set to output RAM value {}", self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER]));
+        self.STATE.DEFINED.P_MUX &= self.CHECK_RAM("MTP", true);
+        let WORD = self.RAM_READ();
+        self.STATE.P_MUX |= WORD;
+        self.log_append(format!("MTP: P-MUX set to output RAM value {}", WORD));
     }
 
     //N-MUX instructions
@@ -396,30 +646,36 @@ impl SYSTEM {
     //Accumulator to N-adder input
     fn ATN(&mut self) {
         self.log_append(format!("ATN: N-MUX set to accumulator value {}", self.STATE.ACCUMULATOR));
-        self.STATE.N_MUX = self.STATE.ACCUMULATOR;
+        self.CHECK_DEFINED(self.STATE.DEFINED.ACCUMULATOR, "ATN: Accumulator");
+        self.STATE.N_MUX |= self.STATE.ACCUMULATOR;
+        self.STATE.DEFINED.N_MUX &= self.STATE.DEFINED.ACCUMULATOR;
     }
 
     //not-accumulator to N-adder input
     fn NATN(&mut self) {
         self.log_append("NATN: N-MUX set to output inverted accumulator".to_string());
-        self.STATE.N_MUX = u4(1 + !(self.STATE.ACCUMULATOR));
+        self.CHECK_DEFINED(self.STATE.DEFINED.ACCUMULATOR, "NATN: Accumulator");
+        self.STATE.N_MUX |= u4(!(self.STATE.ACCUMULATOR)); //ones' complement; subtraction adds CIN
+        self.STATE.DEFINED.N_MUX &= self.STATE.DEFINED.ACCUMULATOR;
     }
 
     //Memory (X, Y) to N-adder input
     fn MTN(&mut self) {
-        self.STATE.N_MUX = self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER];
-        self.log_append(format!("MTN: N-MUX set to output RAM value {}", self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER]));
+        self.STATE.DEFINED.N_MUX &= self.CHECK_RAM("MTN", true);
+        let WORD = self.RAM_READ();
+        self.STATE.N_MUX |= WORD;
+        self.log_append(format!("MTN: N-MUX set to output RAM value {}", WORD));
     }
 
     //F16 to N-adder input
     fn TN15(&mut self) {
-        self.STATE.N_MUX = 15;
+        self.STATE.N_MUX |= 15;
         self.log_append("15TN: N-MUX set to output 15".to_string());
     }
 
     //CKI to N-adder input
     fn CKN(&mut self) {
-        self.STATE.N_MUX = self.CKI();
+        self.STATE.N_MUX |= self.CKI();
         self.log_append("CKN: N-MUX set to output CKI".to_string());
     }
 
@@ -456,17 +712,20 @@ impl SYSTEM {
 
     //Accumulator data to memory
     fn STO(&mut self) {
-        if self.STATE.ACCUMULATOR == 255 {
-            self.log_append("STO: ALERT! Uninitialized value being stored".into());
-        }
-        self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER] = self.STATE.ACCUMULATOR;
-        self.log_append(format!("STO: RAM location {}, {} set to accumulator value {}", self.STATE.X_REGISTER, self.STATE.Y_REGISTER, self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER]));
+        self.CHECK_DEFINED(self.STATE.DEFINED.ACCUMULATOR, "STO: Accumulator");
+        self.CHECK_RAM("STO", false);
+        self.SET_RAM_DEFINED(self.STATE.DEFINED.ACCUMULATOR);
+        self.RAM_WRITE(self.STATE.ACCUMULATOR);
+        self.log_append(format!("STO: RAM location {}, {} set to accumulator value {}", self.STATE.X_REGISTER, self.STATE.Y_REGISTER, self.STATE.ACCUMULATOR));
     }
 
     //CKI to memory
     fn CKM(&mut self) {
-        self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER] = self.CKI();
-        self.log_append(format!("CKM: RAM location {}, {} set to CKI value {}", self.STATE.X_REGISTER, self.STATE.Y_REGISTER, self.STATE.RAM_ARRAY[self.STATE.X_REGISTER][self.STATE.Y_REGISTER]));
+        self.CHECK_RAM("CKM", false);
+        self.SET_RAM_DEFINED(true);
+        let WORD = self.CKI();
+        self.RAM_WRITE(WORD);
+        self.log_append(format!("CKM: RAM location {}, {} set to CKI value {}", self.STATE.X_REGISTER, self.STATE.Y_REGISTER, WORD));
     }
 
     //AU Select/Status latch instructions
@@ -474,18 +733,21 @@ impl SYSTEM {
     //Adder result stored into accumulator
     fn AUTA(&mut self) {
         self.STATE.ACCUMULATOR = self.ADDER().1;
+        self.STATE.DEFINED.ACCUMULATOR = self.STATE.DEFINED.P_MUX && self.STATE.DEFINED.N_MUX;
         self.log_append(format!("AUTA: Accumulator set to adder result {}", self.STATE.ACCUMULATOR));
     }
 
     //Adder result stored into Y-register
     fn AUTY(&mut self) {
         self.STATE.Y_REGISTER = self.ADDER().1 as usize;
+        self.STATE.DEFINED.Y_REGISTER = self.STATE.DEFINED.P_MUX && self.STATE.DEFINED.N_MUX;
         self.log_append(format!("AUTY: Y register set to adder result {}", self.STATE.Y_REGISTER));
     }
 
     //Status is stored into status latch
     fn STSL(&mut self) {
         self.STATE.STATUS_LATCH = self.STATE.STATUS;
+        self.STATE.DEFINED.STATUS_LATCH = true;
         self.log_append(format!("STSL: Status latch set to status value {}", self.STATE.STATUS_LATCH));
     }
 
@@ -495,6 +757,14 @@ impl SYSTEM {
     const TMS1000_instructions : [fn(&mut SYSTEM); 16] = [SYSTEM::STO, SYSTEM::CKM, SYSTEM::CKP, SYSTEM::YTP, SYSTEM::MTP, SYSTEM::ATN, SYSTEM::NATN, SYSTEM::MTN, SYSTEM::TN15, SYSTEM::CKN, SYSTEM::NE, SYSTEM::C8, SYSTEM::CIN, SYSTEM::AUTA, SYSTEM::AUTY, SYSTEM::STSL];
     const TMS1000_mask : u32 = 0b0001001111111100;
 
+    //Runs micro-instruction `i` if the decoded instruction selects it
+    //A halting access fault stops the rest of the instruction cycle, so nothing after it runs
+    fn MICRO_INSTRUCTION(&mut self, i : usize) {
+        if (self.STATE.INSTRUCTION_DECODED & (1 << i) != 0) && !self.STATE.HALTED {
+            SYSTEM::TMS1000_instructions[i](self);
+        }
+    }
+
     //Rom Address
     //Read RAM
     //ALU input
@@ -503,21 +773,20 @@ impl SYSTEM {
         self.STATE.ADDER_INC = 0; //used by CIN; a little clumsy
         self.STATE.P_MUX = 0;
         self.STATE.N_MUX = 0;
+        self.STATE.DEFINED.P_MUX = true;
+        self.STATE.DEFINED.N_MUX = true;
 
         for i in 2..=12 {
-            if (self.STATE.INSTRUCTION_DECODED & (1 << i) != 0) && !(i == 10 || i == 11){
-                SYSTEM::TMS1000_instructions[i](self);
+            if !(i == 10 || i == 11) {
+                self.MICRO_INSTRUCTION(i);
             }
         }
         for i in 10..=11 { //Ensures that NE and C8 only function after all inputs are loaded in
-            if (self.STATE.INSTRUCTION_DECODED & (1 << i)) != 0 {
-                SYSTEM::TMS1000_instructions[i](self);
-            }
+            self.MICRO_INSTRUCTION(i);
         }
 
-        match self.STATE.INSTRUCTION { //Based on timing table, RSTR appears to occur at the falling edge of this osc pulse
-            0x0C => SYSTEM::RSTR(self),
-            _ => ()
+        if self.STATE.INSTRUCTION == 0x0C && !self.STATE.HALTED { //Based on timing table, RSTR appears to occur at the falling edge of this osc pulse
+            SYSTEM::RSTR(self);
         }
     }
 
@@ -529,9 +798,7 @@ impl SYSTEM {
             _ => ()
         }
         for i in 0..=1 {
-            if (self.STATE.INSTRUCTION_DECODED & (1 << i) != 0) {
-                SYSTEM::TMS1000_instructions[i](self);
-            }
+            self.MICRO_INSTRUCTION(i);
         }
     }
 
@@ -543,31 +810,19 @@ impl SYSTEM {
         match self.STATE.INSTRUCTION {
             0x0D => SYSTEM::SETR(self),
             0x0A => SYSTEM::TDO(self),
-            0x0B => (match self.VERSION {
-                1100 | 1300 => SYSTEM::COMC(self),
-                _ => SYSTEM::CLO(self),
+            0x0B => (match self.VERSION.is_TMS1100() {
+                true => SYSTEM::COMC(self),
+                false => SYSTEM::CLO(self),
             }) ,
             0x10..=0x1F => SYSTEM::LDP(self),
-            0x28..=0x2F => (match self.VERSION {
-                1100 | 1300 => SYSTEM::LDX_TMS1100(self),
-                _ => ()
-            }),
-            0x3C..=0x3F => (match self.VERSION {
-                1100 | 1300 => (),
-                _ => SYSTEM::LDX_TMS1000(self),
-            }),
-            0x00 => (if !((1100 == self.VERSION) || (1300 == self.VERSION)) {
-                SYSTEM::COMX(self)
-            }),
-            0x09 => (if ((1100 == self.VERSION) || (1300 == self.VERSION)) {
-                SYSTEM::COMX(self)
-            }),
+            0x28..=0x2F if self.VERSION.is_TMS1100() => SYSTEM::LDX_TMS1100(self),
+            0x3C..=0x3F if !self.VERSION.is_TMS1100() => SYSTEM::LDX_TMS1000(self),
+            0x00 if !self.VERSION.is_TMS1100() => SYSTEM::COMX(self),
+            0x09 if self.VERSION.is_TMS1100() => SYSTEM::COMX(self),
             _ => ()
         }
         for i in 13..=15 {
-            if (self.STATE.INSTRUCTION_DECODED & (1 << i) != 0) {
-                SYSTEM::TMS1000_instructions[i](self);
-            }
+            self.MICRO_INSTRUCTION(i);
         }
         self.INCREMENT_PC();
     }
@@ -575,6 +830,12 @@ impl SYSTEM {
     //Instruction decode
     //Execute BR/CALL
     fn step_6(&mut self) {
+        if let Some(coverage) = self.COVERAGE.as_mut() {
+            if self.STATE.INSTRUCTION >= 0x80 {
+                let (chapter, page, pc) = self.STATE.INSTRUCTION_ADDRESS;
+                coverage.record_branch((1024 * chapter) + (64 * page as usize) + pc, self.STATE.STATUS == 1);
+            }
+        }
         match self.STATE.INSTRUCTION {
             0x80..=0xBF => SYSTEM::BR(self),
             0xC0..=0xFF => SYSTEM::CALL(self),
@@ -591,7 +852,17 @@ impl SYSTEM {
             }
         }
 
-        self.STATE.INSTRUCTION = self.ROM_ARRAY[(1024 * self.STATE.CHAPTER_ADDRESS) + (64 * self.STATE.PAGE_ADDRESS as usize) + self.STATE.PROGRAM_COUNTER];
+        self.STATE.INSTRUCTION = self.ROM_READ((1024 * self.STATE.CHAPTER_ADDRESS) + (64 * self.STATE.PAGE_ADDRESS as usize) + self.STATE.PROGRAM_COUNTER);
+        if self.STATE.HALTED {
+            return;
+        }
+        self.STATE.INSTRUCTION_ADDRESS = (self.STATE.CHAPTER_ADDRESS, self.STATE.PAGE_ADDRESS, self.STATE.PROGRAM_COUNTER);
+        if let Some(coverage) = self.COVERAGE.as_mut() {
+            coverage.record_execution((1024 * self.STATE.CHAPTER_ADDRESS) + (64 * self.STATE.PAGE_ADDRESS as usize) + self.STATE.PROGRAM_COUNTER);
+        }
+        if let Some(profile) = self.PROFILE.as_mut() {
+            profile.record((1024 * self.STATE.CHAPTER_ADDRESS) + (64 * self.STATE.PAGE_ADDRESS as usize) + self.STATE.PROGRAM_COUNTER, self.STATE.CALL_LATCH == 1);
+        }
         self.log_append(format!("Instruction {:0>8b} loaded from ROM address {} {} {}", self.STATE.INSTRUCTION, self.STATE.CHAPTER_ADDRESS, self.STATE.PAGE_ADDRESS, self.STATE.PROGRAM_COUNTER));
 
         self.STATE.INSTRUCTION_DECODED = (match self.INSTRUCTION_PLA.get(&(self.STATE.INSTRUCTION as u32)) {
@@ -606,6 +877,13 @@ impl SYSTEM {
 
 
     pub fn STEP(&mut self, k_inp : u8) -> Self {
+        if self.STATE.INIT_PIN > 0 {
+            self.HOLD_INIT(1);
+            return self.clone();
+        }
+        if self.STATE.HALTED {
+            return self.clone();
+        }
         self.STATE.K_INPUT = k_inp;
         self.log_append(format!("Executing step {}", self.STATE.STEP));
         SYSTEM::steps[self.STATE.STEP](self);
@@ -614,6 +892,13 @@ impl SYSTEM {
     }
 
     pub fn STEP_mut(&mut self, k_inp : u8) {
+        if self.STATE.INIT_PIN > 0 {
+            self.HOLD_INIT(1);
+            return;
+        }
+        if self.STATE.HALTED {
+            return;
+        }
         self.STATE.K_INPUT = k_inp;
         self.log_append(format!("Executing step {}", self.STATE.STEP));
         SYSTEM::steps[self.STATE.STEP](self);
@@ -622,14 +907,28 @@ impl SYSTEM {
 
     //completes one full instruction cycle
     pub fn instruction_cycle(&mut self, k_inp : u8) -> Self {
-        while (self.STATE.STEP < 3) {
+        if self.STATE.INIT_PIN > 0 {
+            self.HOLD_INIT(4);
+            return self.clone();
+        }
+        if self.STATE.HALTED {
+            return self.clone();
+        }
+        while (self.STATE.STEP < 3) && !self.STATE.HALTED {
             self.STEP(k_inp);
         }
         return self.STEP(k_inp);
     }
 
     pub fn instruction_cycle_mut(&mut self, k_inp : u8) {
-        while (self.STATE.STEP < 3) {
+        if self.STATE.INIT_PIN > 0 {
+            self.HOLD_INIT(4);
+            return;
+        }
+        if self.STATE.HALTED {
+            return;
+        }
+        while (self.STATE.STEP < 3) && !self.STATE.HALTED {
             self.STEP_mut(k_inp);
         }
         self.STEP_mut(k_inp);
@@ -650,6 +949,11 @@ impl SYSTEM {
         return rval;
     }
 
+    //The O register as TDO left it (status latch, then the accumulator), before the output PLA
+    pub fn get_o_register(&mut self) -> u32 {
+        return self.STATE.O_OUTPUT;
+    }
+
     pub fn get_r_outputs_vec(&mut self) -> Vec<u8>  {
         return self.STATE.R_OUTPUT.clone();
     }
@@ -666,6 +970,16 @@ impl SYSTEM {
         return (self.STATE.CHAPTER_ADDRESS * 1024) + (self.STATE.PAGE_ADDRESS as usize * 64) + self.STATE.PC_INDEX;
     }
 
+    //The instruction the next cycle executes, and the ROM index (chapter, page, raw PC) it was fetched from
+    pub fn get_instruction(&mut self) -> u8 {
+        return self.STATE.INSTRUCTION;
+    }
+
+    pub fn get_instruction_address(&mut self) -> usize {
+        let (chapter, page, pc) = self.STATE.INSTRUCTION_ADDRESS;
+        return (1024 * chapter) + (64 * page as usize) + pc;
+    }
+
     pub fn get_log(&mut self) -> Vec<String> {
         let retval = self.STATE.LOG.clone();
         self.STATE.LOG = Vec::new();
@@ -677,59 +991,59 @@ impl SYSTEM {
     }
 
     pub fn get_ram_array(&mut self) -> [[u8; 16]; 8] {
-        return self.STATE.RAM_ARRAY.clone();
+        return self.STATE.RAM_ARRAY;
     }
 
     pub fn get_x_reg(&mut self) -> usize {
-        return self.STATE.X_REGISTER.clone();
+        return self.STATE.X_REGISTER;
     }
 
     pub fn get_y_reg(&mut self) -> usize {
-        return self.STATE.Y_REGISTER.clone();
+        return self.STATE.Y_REGISTER;
     }
 
     pub fn get_pa_reg(&mut self) -> u8 {
-        return self.STATE.PAGE_ADDRESS.clone();
+        return self.STATE.PAGE_ADDRESS;
     }
 
     pub fn get_pb_reg(&mut self) -> u8 {
-        return self.STATE.PAGE_BUFFER.clone();
+        return self.STATE.PAGE_BUFFER;
     }
 
     pub fn get_cl_reg(&mut self) -> u8 {
-        return self.STATE.CALL_LATCH.clone();
+        return self.STATE.CALL_LATCH;
     }
 
     pub fn get_acc_reg(&mut self) -> u8 {
-        return self.STATE.ACCUMULATOR.clone();
+        return self.STATE.ACCUMULATOR;
     }
 
     pub fn get_status(&mut self) -> u8 {
-        return self.STATE.STATUS.clone();
+        return self.STATE.STATUS;
     }
 
     pub fn get_sl_reg(&mut self) -> u8 {
-        return self.STATE.STATUS_LATCH.clone();
+        return self.STATE.STATUS_LATCH;
     }
 
     pub fn get_ca_reg(&mut self) -> usize {
-        return self.STATE.CHAPTER_ADDRESS.clone();
+        return self.STATE.CHAPTER_ADDRESS;
     }
 
     pub fn get_cb_reg(&mut self) -> usize {
-        return self.STATE.CHAPTER_BUFFER.clone();
+        return self.STATE.CHAPTER_BUFFER;
     }
 
     pub fn get_csl_reg(&mut self) -> usize {
-        return self.STATE.CHAPTER_SUBROUTINE_LATCH.clone();
+        return self.STATE.CHAPTER_SUBROUTINE_LATCH;
     }
 
     pub fn get_pc_reg(&mut self) -> usize {
-        return self.STATE.PROGRAM_COUNTER.clone();
+        return self.STATE.PROGRAM_COUNTER;
     }
 
     pub fn get_sr_reg(&mut self) -> usize {
-        return self.STATE.SUBROUTINE_RETURN.clone();
+        return self.STATE.SUBROUTINE_RETURN;
     }
 
     pub fn get_ipla(&mut self) -> HashMap<u32, u32> {
@@ -744,50 +1058,199 @@ impl SYSTEM {
         self.logging = !self.logging;
     }
 
-    //Replicates INIT pin behavior
-    pub fn INITIALIZE(&mut self) {
-        self.log_append("Hardware reinitialized".to_string());
+    //Flags every read of RAM, the accumulator, X, Y or the status latch before it has been written
+    //Reads are logged as ALERTs and collected for get_undefined_reads
+    pub fn set_definedness_tracking(&mut self, enabled : bool) {
+        self.definedness = enabled;
+    }
+
+    //Counts executions of every ROM byte (as it is fetched) and the outcome of every BR/CALL
+    //Disabling discards what has been recorded
+    pub fn set_coverage(&mut self, enabled : bool) {
+        self.COVERAGE = match (enabled, self.COVERAGE.take()) {
+            (true, Some(v)) => Some(v),
+            (true, None) => Some(Coverage::new()),
+            (false, _) => None,
+        };
+    }
+
+    pub fn get_coverage(&mut self) -> Option<Coverage> {
+        return self.COVERAGE.clone();
+    }
+
+    //Charges every instruction cycle to its ROM address and subroutine, see profiler.rs
+    //Disabling discards the profile
+    pub fn set_profiling(&mut self, enabled : bool) {
+        self.PROFILE = match (enabled, self.PROFILE.take()) {
+            (true, Some(v)) => Some(v),
+            (true, None) => Some(Profile::new()),
+            (false, _) => None,
+        };
+    }
+
+    pub fn get_profile(&mut self) -> Option<Profile> {
+        return self.PROFILE.clone();
+    }
+
+    pub fn get_undefined_reads(&mut self) -> Vec<UndefinedRead> {
+        let retval = self.UNDEFINED_READS.clone();
+        self.UNDEFINED_READS = Vec::new();
+        return retval;
+    }
+
+    //Why the ROM image did not fit the chip, if it did not
+    pub fn get_rom_warning(&self) -> Option<String> {
+        return self.ROM_WARNING.clone();
+    }
+
+    //Wrap unless set otherwise
+    pub fn set_access_policy(&mut self, policy : AccessPolicy) {
+        self.ACCESS_POLICY = policy;
+    }
+
+    pub fn get_access_policy(&self) -> AccessPolicy {
+        return self.ACCESS_POLICY;
+    }
+
+    //Drains the faults recorded under AccessPolicy::Halt, like get_undefined_reads
+    pub fn get_access_faults(&mut self) -> Vec<AccessFault> {
+        let retval = self.ACCESS_FAULTS.clone();
+        self.ACCESS_FAULTS = Vec::new();
+        return retval;
+    }
+
+    //true after an access fault, until INIT
+    pub fn is_halted(&self) -> bool {
+        return self.STATE.HALTED;
+    }
+
+    //Puts the control registers into their power-on state
+    //RAM, the accumulator, X, Y and the status latch are not affected by INIT and are left as they were
+    fn RESET(&mut self) {
+        self.STATE.STEP = 0;
+        self.STATE.HALTED = false;
+        self.STATE.INSTRUCTION = 127; //should function as a no-op until incremented
+        self.STATE.INSTRUCTION_ADDRESS = (0, 15, PC_SEQ[63]);
+        self.STATE.INSTRUCTION_DECODED = 0;
+        self.STATE.PROGRAM_COUNTER = PC_SEQ[63];
+        self.STATE.PC_INDEX = 63; //Starts at last instruction so on first cycle increment will go to first instruction; as far as I can tell this is how the actual hardware did it too
+        self.STATE.SUBROUTINE_RETURN = 0;
         self.STATE.PAGE_ADDRESS = 15;
         self.STATE.PAGE_BUFFER = 15;
-        self.STATE.PROGRAM_COUNTER = 0;
-        self.STATE.PC_INDEX = 0;
-        self.STATE.CHAPTER_ADDRESS = 0;
+        self.STATE.CHAPTER_ADDRESS = 0; //On non TMS1100/1300 systems these will never be changed
         self.STATE.CHAPTER_BUFFER = 0;
         self.STATE.CHAPTER_SUBROUTINE_LATCH = 0;
         self.STATE.CALL_LATCH = 0;
-        self.STATE.R_OUTPUT = (match self.VERSION {
-                    1200 | 1270 => vec![0; 13],
-                    1300 => vec![0; 16],
-                    _ => vec![0; 11],
-                    });
+        self.STATE.P_MUX = 0;
+        self.STATE.N_MUX = 0;
+        self.STATE.ADDER_INC = 0;
+        self.STATE.STATUS = 1;
+        self.STATE.STATUS_LIFETIME = 0;
+        self.STATE.R_OUTPUT = vec![0; self.VERSION.r_output_count()];
         self.STATE.O_OUTPUT = 0;
-        self.STATE.CALL_LATCH = 0;
+    }
+
+    //Fills RAM, the accumulator, X, Y and the status latch according to the power-on policy
+    fn POWER_ON(&mut self, policy : PowerOnPolicy) {
+        match policy {
+            PowerOnPolicy::Zero => {
+                self.STATE.RAM_ARRAY = [[0; 16]; 8];
+                self.STATE.X_REGISTER = 0;
+                self.STATE.Y_REGISTER = 0;
+                self.STATE.STATUS_LATCH = 0;
+                self.STATE.ACCUMULATOR = 0;
+            },
+            PowerOnPolicy::Sentinel => {
+                self.STATE.RAM_ARRAY = [[255; 16]; 8]; //this and all below are set to an invalid value, must be properly initialized by code
+                self.STATE.X_REGISTER = 255;
+                self.STATE.Y_REGISTER = 255;
+                self.STATE.STATUS_LATCH = 255;
+                self.STATE.ACCUMULATOR = 255;
+            },
+            PowerOnPolicy::Random(seed) => {
+                let mut state = seed;
+                for file in self.STATE.RAM_ARRAY.iter_mut() {
+                    for word in file.iter_mut() {
+                        *word = u4(next_random(&mut state) as u8);
+                    }
+                }
+                self.STATE.X_REGISTER = (next_random(&mut state) % self.VERSION.ram_files() as u64) as usize;
+                self.STATE.Y_REGISTER = (next_random(&mut state) % 16) as usize;
+                self.STATE.STATUS_LATCH = (next_random(&mut state) % 2) as u8;
+                self.STATE.ACCUMULATOR = (next_random(&mut state) % 16) as u8;
+            },
+        }
+        self.STATE.DEFINED = DEFINEDNESS::power_on();
+        self.log_append(format!("Powered on with {:?} RAM and registers", policy));
+    }
+
+    //Keeps the chip in reset while the INIT pin is high, consuming up to `steps` steps of the hold time
+    fn HOLD_INIT(&mut self, steps : u32) {
+        self.RESET();
+        self.STATE.INIT_PIN = self.STATE.INIT_PIN.saturating_sub(steps);
+        if self.STATE.INIT_PIN == 0 {
+            self.log_append("INIT: Released, execution resumes from page 15, PC 0".to_string());
+        }
+        else {
+            self.log_append(format!("INIT: Held in reset ({} steps remaining)", self.STATE.INIT_PIN));
+        }
+    }
+
+    //Replicates INIT pin behavior
+    //Holds the INIT pin high for the given number of instruction cycles; the chip resets immediately and
+    //resumes execution on the first cycle after release, exactly as it does after power-on
+    //Holds longer than u32::MAX steps are cut to that
+    pub fn set_init(&mut self, cycles : u32) {
+        self.STATE.INIT_PIN = cycles.saturating_mul(4);
+        self.RESET();
+        self.log_append(format!("INIT: Held for {} instruction cycles", cycles));
+    }
+
+    //Immediate reinitialization, equivalent to pulsing the INIT pin
+    pub fn INITIALIZE(&mut self) {
+        self.log_append("Hardware reinitialized".to_string());
+        self.STATE.INIT_PIN = 0;
+        self.RESET();
+    }
+
+    pub fn get_init(&mut self) -> bool {
+        return self.STATE.INIT_PIN > 0;
     }
 
     //Reads PLA into a HashMap
     //Used in initialization (below)
-    fn read_PLA(filename : String) -> Result<HashMap<u32, u32>, String> {
+    pub(crate) fn read_PLA(filename : String) -> Result<HashMap<u32, u32>, String> {
         let data: String =  match fs::read_to_string(filename) {
             Ok(v) => v,
             Err(_) => return Err("Problem opening or reading PLA file".to_string()),
         };
+        return Self::parse_PLA(&data);
+    }
+
+    //Parses the text of a PLA file: one "inputs outputs" term per line, inputs being 0, 1 or - (either)
+    pub fn parse_PLA(data : &str) -> Result<HashMap<u32, u32>, String> {
         let re = Regex::new(r"([\-0-1]+) ([0-1]+)").unwrap(); //Unwrapping a static valid regex should be safe
         let mut pla_table = HashMap::new();
 
-        for line in re.captures_iter(&data) {
-            let mut inputs = Vec::new();
-            inputs.push(0b0);
-            let output = u32::from_str_radix(line[2].as_ref(), 2).unwrap(); //Should be guarenteed by regex
+        for line in re.captures_iter(data) {
+            let mut inputs = vec![0b0];
+            if line[1].len() > 16 || line[2].len() > 32 {
+                return Err(format!("Term \"{} {}\" is wider than 16 inputs or 32 outputs", &line[1], &line[2]));
+            }
+            let output = match u32::from_str_radix(line[2].as_ref(), 2) {
+                Ok(v) => v,
+                Err(_) => return Err(format!("Could not read output \"{}\"", &line[2])),
+            };
 
 
-            if !(output == 0) { //empty lines are skipped over
+            if output != 0 { //empty lines are skipped over
                 for ch in line[1].chars().rev() {
                     if ch == '-' {
                         let mut input0 = Vec::new();
                         let mut input1 = Vec::new();
-                        for ref input in &inputs {
-                            input0.push(*input << 1);
-                            input1.push((*input << 1) + 1);
+                        for input in &inputs {
+                            input0.push(input << 1);
+                            input1.push((input << 1) + 1);
                         }
                         inputs = Vec::new();
                         inputs.append(&mut input1);
@@ -810,7 +1273,7 @@ impl SYSTEM {
                         pla_table.insert(input, output);
                     }
                     else {
-                        let combined_output : u32 = output | pla_table.get(&input).unwrap();
+                        let combined_output : u32 = output | pla_table.get(&input).copied().unwrap_or(0);
                         //Bitwise ORs overlapping PLA terms
                         //Since if one term activates (and brings the instruction line to 1), the output line will be 1
                         pla_table.insert(input, combined_output);
@@ -822,8 +1285,24 @@ impl SYSTEM {
         Ok(pla_table)
     }
 
-    pub fn load_system(version: u32, rom_file : String, ipla_file : String, opla_file : String) -> Result<Self, String> {
+    pub fn load_system(version: ChipVariant, rom_file : String, ipla_file : String, opla_file : String) -> Result<Self, String> {
+        return Self::load_system_with_policy(version, rom_file, ipla_file, opla_file, PowerOnPolicy::Sentinel);
+    }
+
+    pub fn load_system_with_policy(version: ChipVariant, rom_file : String, ipla_file : String, opla_file : String, policy : PowerOnPolicy) -> Result<Self, String> {
+        let mut rFile = match fs::File::open(rom_file) {
+            Ok(v) => v,
+            Err(_) => return Err("ROM error: Problem opening ROM file".to_owned()),
+        };
+
+        let mut rom_array = vec![];
+        let _ = rFile.read_to_end(&mut rom_array);
 
+        return Self::load_system_from_rom(version, rom_array, ipla_file, opla_file, policy);
+    }
+
+    //As load_system_with_policy, with a ROM image already in memory (e.g. fresh from the assembler)
+    pub fn load_system_from_rom(version: ChipVariant, rom_array : Vec<u8>, ipla_file : String, opla_file : String, policy : PowerOnPolicy) -> Result<Self, String> {
         let iPLA = match Self::read_PLA(ipla_file) {
             Ok(v) => v,
             Err(v) => {
@@ -838,54 +1317,61 @@ impl SYSTEM {
                 return Err(e)},
         };
 
-        let mut rFile = match fs::File::open(rom_file) {
-            Ok(v) => v,
-            Err(_) => return Err("ROM error: Problem opening ROM file".to_owned()),
-        };
-
-        let mut rom_array = vec![];
-        let _ = rFile.read_to_end(&mut rom_array);
+        return Self::from_tables(version, rom_array, iPLA, oPLA, policy);
+    }
 
+    //As load_system_from_rom, with PLAs already parsed by parse_PLA
+    pub fn from_tables(version: ChipVariant, rom_array : Vec<u8>, iPLA : HashMap<u32, u32>, oPLA : HashMap<u32, u32>, policy : PowerOnPolicy) -> Result<Self, String> {
+        let (rom_array, rom_warning) = version.fit_rom(&rom_array);
 
-        let sys = SYSTEM {
+        let mut sys = SYSTEM {
             VERSION: version,
             STATE: SYSTEM_STATE {
                 LOG : Vec::new(),
                 STEP : 0,
-                INSTRUCTION : 127, //should function as a no-op until incremented
+                INSTRUCTION : 0,
+                INSTRUCTION_ADDRESS : (0, 0, 0),
                 INSTRUCTION_DECODED : 0,
-                PROGRAM_COUNTER: 0x20,
-                PC_INDEX: 63, //Starts at last instruction so on first cycle increment will go to first instruction; as far as I can tell this is how the actual hardware did it too
+                PROGRAM_COUNTER: 0,
+                PC_INDEX: 0,
                 SUBROUTINE_RETURN : 0,
-                PAGE_ADDRESS: 15,
-                PAGE_BUFFER: 15,
-                CHAPTER_ADDRESS: 0, //On non TMS1100/1300 systems these will never be changed
+                PAGE_ADDRESS: 0,
+                PAGE_BUFFER: 0,
+                CHAPTER_ADDRESS: 0,
                 CHAPTER_BUFFER: 0,
                 CHAPTER_SUBROUTINE_LATCH: 0,
                 P_MUX: 0,
                 N_MUX: 0,
                 CALL_LATCH: 0,
-                R_OUTPUT: (match version {
-                    1200 | 1270 => vec![0; 13],
-                    1300 => vec![0; 16],
-                    _ => vec![0; 11],
-                    }),
+                R_OUTPUT: Vec::new(),
                 O_OUTPUT: 0,
                 STATUS: 1,
                 STATUS_LIFETIME: 0,
                 ADDER_INC: 0,
                 K_INPUT: 0,
-                RAM_ARRAY: [[255; 16]; 8], //this and all below are set to an invalid value, must be properly initialized by code
-                X_REGISTER: 255,
-                Y_REGISTER: 255,
-                STATUS_LATCH: 255,
-                ACCUMULATOR: 255,
+                INIT_PIN: 0,
+                HALTED: false,
+                DEFINED: DEFINEDNESS::power_on(),
+                RAM_ARRAY: [[0; 16]; 8], //this and all below are set by the power-on policy
+                X_REGISTER: 0,
+                Y_REGISTER: 0,
+                STATUS_LATCH: 0,
+                ACCUMULATOR: 0,
             },
             ROM_ARRAY: rom_array,
+            ROM_WARNING: rom_warning,
             INSTRUCTION_PLA: iPLA,
             OUTPUT_PLA: oPLA,
             logging: true,
+            definedness: false,
+            UNDEFINED_READS: Vec::new(),
+            ACCESS_POLICY: AccessPolicy::Wrap,
+            ACCESS_FAULTS: Vec::new(),
+            COVERAGE: None,
+            PROFILE: None,
         };
+        sys.POWER_ON(policy);
+        sys.RESET(); //Power-on goes through the same reset as the INIT pin
 
         return Ok(sys);
     }
@@ -0,0 +1,725 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(unused_parens)]
+#![allow(clippy::needless_return)]
+//Symbolic assembler
+//Unlike compiler::compile_TMS1000/1100, source is written in execution order and the assembler
+//places each instruction at the right PC_SEQ position of its page.
+//
+//Source format, one statement per line:
+//    label:  MNEMONIC operand    ; comment
+//Labels end with a colon and may stand on their own line. Operands are numbers or symbols: labels
+//(for BR and CALL) and constants defined with .equ. Numbers, here and in directives, are decimal,
+//hexadecimal ("0x1F" or "$1F"), binary ("0b1010" or "%1010") or character constants ('A').
+//Code starts at the reset vector (chapter 0, page 15, PC 0). Each page fills up in execution order,
+//and overflowing one is an error.
+//
+//Directives:
+//    .org chapter,page    continue on the given page
+//    .page                continue on the next unused page of the chapter, after page 15 page 0,
+//                         unless nothing has been placed on the current one
+//    .equ NAME value      define a constant
+//    .byte value, ...     place raw bytes
+//    .fill count[,value]  place count copies of a raw byte (default 0)
+//    .include file        assemble another file in place, relative to the including file
+//
+//Macros are defined with
+//    .macro NAME param, ...
+//        ...
+//    .endm
+//and invoked like an instruction, "NAME arg, ...". In the body, \param is replaced by the argument
+//and \@ by a number unique to each expansion, so "skip\@:" gives every expansion its own local label.
+//Macros are expanded before anything is placed, and may invoke other macros.
+//BR/CALL to a label on another page are automatically preceded by an LDP of the target page. When the
+//instruction before the branch can clear status, the LDP goes before that instruction instead, and such
+//a branch cannot carry a label of its own. A branch that is not taken leaves the other page loaded, so
+//the next branch to a label, even on the same page, gets an LDP too.
+//Inside a subroutine the call latch is set: BR and CALL stay on the current page, and the page buffer
+//holds the return page, so an LDP there would send RETN to the wrong page. A BR/CALL to a label on another
+//page in code reachable from a CALL target before its RETN is reported instead of given an LDP.
+//Branches into the other chapter of a TMS1100/1300 must be preceded by a COMC written by hand. The
+//assembler counts COMCs since the last unconditional BR or RETN, and reports a branch into the other
+//chapter after an even number of them, or one within the chapter after an odd number, which is what
+//falling through an untaken branch into the other chapter leaves without a second COMC.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use regex::Regex;
+
+use crate::cfg;
+use crate::compiler;
+use crate::compiler::Diagnostic;
+use crate::listing::{Assembled, ListingLine, Symbol};
+use crate::variant::ChipVariant;
+use crate::TMS1000::PC_SEQ;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Operand {
+    None,
+    Number(usize),
+    Symbol(String), //label or constant
+}
+
+//Where a statement came from, for error messages
+#[derive(Clone, Debug)]
+struct Location {
+    file: String,
+    line: usize,
+    column: usize, //of the first character of the statement
+}
+
+#[derive(Clone, Debug)]
+struct SourceLine {
+    location: Location,
+    text: String,
+    expanded_from: Option<String>, //macro that produced the line
+}
+
+#[derive(Clone, Debug)]
+struct Macro {
+    params: Vec<String>,
+    body: Vec<SourceLine>,
+}
+
+#[derive(Clone, Debug)]
+struct Statement {
+    location: Location,
+    chapter: usize,
+    page: usize,
+    mnemonic: String, //.BYTE for raw data
+    operand: Operand,
+    source: String, //for the listing
+}
+
+#[derive(Clone, Debug)]
+struct Label {
+    name: String,
+    location: Location,
+    statement: usize, //index of the statement it labels
+}
+
+//Parsed source
+struct Program {
+    statements: Vec<Statement>,
+    labels: Vec<Label>,
+    constants: HashMap<String, usize>,
+}
+
+//Location of an assembled instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Address {
+    pub chapter: usize,
+    pub page: usize,
+    pub index: usize, //execution order within the page
+}
+
+impl Address {
+    pub fn pc(&self) -> usize {
+        return PC_SEQ[self.index];
+    }
+
+    pub fn rom_index(&self) -> usize {
+        return (1024 * self.chapter) + (64 * self.page) + self.pc();
+    }
+}
+
+const MAX_INCLUDE_DEPTH : usize = 16;
+const MAX_MACRO_DEPTH : usize = 16;
+
+fn error(location : &Location, message : String) -> Diagnostic {
+    return Diagnostic { file: location.file.clone(), line: location.line, column: location.column, message };
+}
+
+//Code part of a line; a ';' in a character constant does not start a comment
+fn strip_comment(raw : &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in raw.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            ';' if !quoted => return &raw[..i],
+            _ => (),
+        }
+    }
+    return raw;
+}
+
+//Splits arguments on commas (and whitespace, unless commas_only), leaving character constants whole
+fn split_args(text : &str, commas_only : bool) -> Vec<&str> {
+    let mut results: Vec<&str> = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        if c == '\'' {
+            quoted = !quoted;
+        }
+        else if !quoted && (c == ',' || (!commas_only && c.is_whitespace())) {
+            results.push(text[start..i].trim());
+            start = i + c.len_utf8();
+        }
+    }
+    results.push(text[start..].trim());
+    return results.into_iter().filter(|a| !a.is_empty()).collect();
+}
+
+//Reads the source into lines, expanding .include directives
+fn read_source(source : &str, file : &str, directory : &Path, depth : usize, lines : &mut Vec<SourceLine>) -> Result<(), Diagnostic> {
+    let include_re = Regex::new(r#"^(?i:\.include)\s+"?([^"]+?)"?$"#).unwrap();
+    for (idx, raw) in source.lines().enumerate() {
+        let code = strip_comment(raw);
+        let text = code.trim();
+        let column = code.len() - code.trim_start().len() + 1;
+        let location = Location { file: file.to_string(), line: idx + 1, column };
+        if let Some(v) = include_re.captures(text) {
+            if depth >= MAX_INCLUDE_DEPTH {
+                return Err(error(&location, "Includes nested too deeply (is a file including itself?)".to_string()));
+            }
+            let path: PathBuf = directory.join(&v[1]);
+            let included = match fs::read_to_string(&path) {
+                Ok(data) => data,
+                Err(_) => return Err(error(&location, format!("Could not read included file {}", path.display()))),
+            };
+            let included_directory = path.parent().unwrap_or(directory).to_path_buf();
+            read_source(&included, &path.display().to_string(), &included_directory, depth + 1, lines)?;
+            continue;
+        }
+        lines.push(SourceLine { location, text: text.to_string(), expanded_from: None });
+    }
+    return Ok(());
+}
+
+//Substitutes one macro invocation
+fn expand_macro(name : &str, definition : &Macro, args : &[&str], invocation : &SourceLine, expansion : usize) -> Vec<SourceLine> {
+    let mut results: Vec<SourceLine> = Vec::new();
+    for line in definition.body.iter() {
+        let mut text = line.text.replace("\\@", &format!("_{}", expansion));
+        //Longest names first, so that \count is not clobbered by \c
+        let mut params: Vec<(&String, &str)> = definition.params.iter().zip(args.iter().copied()).collect();
+        params.sort_by_key(|p| std::cmp::Reverse(p.0.len()));
+        for (param, arg) in params {
+            text = text.replace(&format!("\\{}", param), arg);
+        }
+        results.push(SourceLine {
+            location: invocation.location.clone(),
+            text,
+            expanded_from: Some(name.to_string()),
+        });
+    }
+    return results;
+}
+
+//Collects macro definitions and expands every invocation
+fn expand_macros(lines : Vec<SourceLine>) -> Result<Vec<SourceLine>, Diagnostic> {
+    let macro_re = Regex::new(r"^(?i:\.macro)\s+([A-Za-z_][A-Za-z0-9_]*)\s*(.*)$").unwrap();
+    let endm_re = Regex::new(r"^(?i:\.endm)$").unwrap();
+    let label_re = Regex::new(r"^([A-Za-z_][A-Za-z0-9_]*:)\s*(.*)$").unwrap();
+    let invocation_re = Regex::new(r"^([A-Za-z_][A-Za-z0-9_]*)\s*(.*)$").unwrap();
+
+    let mut macros: HashMap<String, Macro> = HashMap::new();
+    let mut defining: Option<(String, Location, Macro)> = None;
+    let mut pending: Vec<(SourceLine, usize)> = lines.into_iter().rev().map(|l| (l, 0)).collect(); //stack, with expansion depth
+    let mut results: Vec<SourceLine> = Vec::new();
+    let mut expansions: usize = 0;
+
+    while let Some((line, depth)) = pending.pop() {
+        if let Some((name, location, mut definition)) = defining.take() {
+            if endm_re.is_match(&line.text) {
+                macros.insert(name, definition);
+            }
+            else if macro_re.is_match(&line.text) {
+                return Err(error(&line.location, "Macro definitions cannot be nested".to_string()));
+            }
+            else {
+                definition.body.push(line);
+                defining = Some((name, location, definition));
+            }
+            continue;
+        }
+        if let Some(v) = macro_re.captures(&line.text) {
+            let name = v[1].to_uppercase();
+            if macros.contains_key(&name) {
+                return Err(error(&line.location, format!("Macro {} defined more than once", name)));
+            }
+            if compiler::compile_instruction_TMS1000(name.clone(), 0).is_some() || compiler::compile_instruction_TMS1100(name.clone(), 0).is_some() {
+                return Err(error(&line.location, format!("Macro {} has the name of an instruction", name)));
+            }
+            let params: Vec<String> = v[2].split(|c: char| c == ',' || c.is_whitespace()).filter(|a| !a.is_empty()).map(|a| a.to_string()).collect();
+            defining = Some((name, line.location.clone(), Macro { params, body: Vec::new() }));
+            continue;
+        }
+        if endm_re.is_match(&line.text) {
+            return Err(error(&line.location, ".endm without .macro".to_string()));
+        }
+
+        //A label in front of an invocation labels the first line of the expansion
+        let (label, rest) = match label_re.captures(&line.text) {
+            Some(v) => (Some(v[1].to_string()), v[2].to_string()),
+            None => (None, line.text.clone()),
+        };
+        let invoked = match invocation_re.captures(&rest) {
+            Some(v) => macros.get(&v[1].to_uppercase()).map(|m| (v[1].to_uppercase(), m.clone(), v[2].to_string())),
+            None => None,
+        };
+        let (name, definition, arg_text) = match invoked {
+            Some(v) => v,
+            None => {
+                results.push(line);
+                continue;
+            },
+        };
+        if depth >= MAX_MACRO_DEPTH {
+            return Err(error(&line.location, format!("Macro {} nested too deeply (does it invoke itself?)", name)));
+        }
+        let args: Vec<&str> = split_args(&arg_text, true);
+        if args.len() != definition.params.len() {
+            return Err(error(&line.location, format!("Macro {} takes {} arguments, {} given", name, definition.params.len(), args.len())));
+        }
+        expansions += 1;
+        for expanded in expand_macro(&name, &definition, &args, &line, expansions).into_iter().rev() {
+            pending.push((expanded, depth + 1));
+        }
+        if let Some(v) = label {
+            pending.push((SourceLine { text: v, ..line.clone() }, depth));
+        }
+    }
+    if let Some((name, location, _)) = defining {
+        return Err(error(&location, format!("Macro {} has no .endm", name)));
+    }
+    return Ok(results);
+}
+
+//Source with includes and macros expanded, each line annotated with where it came from
+fn listing_source(lines : &[SourceLine]) -> String {
+    let mut results = String::new();
+    for line in lines.iter() {
+        if line.text.is_empty() {
+            continue;
+        }
+        let origin = match &line.expanded_from {
+            Some(name) => format!("{}:{} ({})", line.location.file, line.location.line, name),
+            None => format!("{}:{}", line.location.file, line.location.line),
+        };
+        results.push_str(&format!("{:<32} ; {}\n", line.text, origin));
+    }
+    return results;
+}
+
+//Names start with a letter or underscore, so anything else was meant as a number
+fn undefined(kind : &str, token : &str) -> String {
+    return match token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        true => format!("Undefined {} {}", kind, token),
+        false => format!("Invalid number {}", token),
+    };
+}
+
+//Numeric value of a directive argument, which may name an earlier constant
+fn directive_value(token : &str, constants : &HashMap<String, usize>, location : &Location) -> Result<usize, Diagnostic> {
+    if let Some(v) = compiler::parse_number(token) {
+        return Ok(v);
+    }
+    return match constants.get(token) {
+        Some(v) => Ok(*v),
+        None => Err(error(location, undefined("constant", token))),
+    };
+}
+
+fn operand(token : &str) -> Operand {
+    return match compiler::parse_number(token) {
+        Some(v) => Operand::Number(v),
+        None => Operand::Symbol(token.to_string()),
+    };
+}
+
+//Applies a directive, returning the raw bytes it places
+fn directive(name : &str, args : &[&str], location : &Location, (chapter, page) : (&mut usize, &mut usize), constants : &mut HashMap<String, usize>, used_pages : &HashSet<(usize, usize)>) -> Result<Vec<Operand>, Diagnostic> {
+    let name_re = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+    let mut data: Vec<Operand> = Vec::new();
+    match (name, args.len()) {
+        ("org", 2) => {
+            (*chapter, *page) = match (directive_value(args[0], constants, location)?, directive_value(args[1], constants, location)?) {
+                (c, p) if c < 2 && p < 16 => (c, p),
+                (c, p) => return Err(error(location, format!("No chapter {}, page {}", c, p))),
+            };
+        },
+        ("page", 0) => {
+            if used_pages.contains(&(*chapter, *page)) {
+                *page = match (1..16).map(|i| (*page + i) % 16).find(|p| !used_pages.contains(&(*chapter, *p))) {
+                    Some(v) => v,
+                    None => return Err(error(location, format!("Every page of chapter {} is in use", chapter))),
+                };
+            }
+        },
+        ("equ", 2) => {
+            if !name_re.is_match(args[0]) {
+                return Err(error(location, format!("Invalid constant name {}", args[0])));
+            }
+            if constants.contains_key(args[0]) {
+                return Err(error(location, format!("Constant {} defined more than once", args[0])));
+            }
+            let value = directive_value(args[1], constants, location)?;
+            constants.insert(args[0].to_string(), value);
+        },
+        ("byte", 1..) => {
+            for arg in args.iter() {
+                data.push(operand(arg));
+            }
+        },
+        ("fill", 1 | 2) => {
+            let count = directive_value(args[0], constants, location)?;
+            let value = match args.get(1) {
+                Some(v) => operand(v),
+                None => Operand::Number(0),
+            };
+            data = vec![value; count];
+        },
+        (name, _) => return Err(error(location, format!("Unknown directive or wrong number of arguments: .{}", name))),
+    }
+    return Ok(data);
+}
+
+//Splits the source into labels, constants and statements
+//A bad line is reported and skipped, so that every one is found in a single run
+fn parse(lines : &[SourceLine]) -> Result<Program, Vec<Diagnostic>> {
+    let label_re = Regex::new(r"^([A-Za-z_][A-Za-z0-9_]*):").unwrap();
+    let statement_re = Regex::new(r"^([A-Za-z0-9]+)(?:\s+([A-Za-z0-9_$%]+|'[^']'))?$").unwrap();
+    let directive_re = Regex::new(r"^\.([A-Za-z]+)\s*(.*)$").unwrap();
+
+    let mut statements: Vec<Statement> = Vec::new();
+    let mut labels: Vec<Label> = Vec::new();
+    let mut constants: HashMap<String, usize> = HashMap::new();
+    let mut used_pages: HashSet<(usize, usize)> = HashSet::new();
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let (mut chapter, mut page) = (0, 15);
+
+    for source_line in lines {
+        let location = &source_line.location;
+        let mut text = source_line.text.as_str();
+        let mut column = location.column;
+
+        if let Some(v) = label_re.captures(text) {
+            labels.push(Label { name: v[1].to_string(), location: location.clone(), statement: statements.len() });
+            let rest = &text[v[0].len()..];
+            column += v[0].len() + rest.len() - rest.trim_start().len();
+            text = rest.trim();
+        }
+        let location = &Location { column, ..location.clone() };
+        if text.is_empty() {
+            continue;
+        }
+
+        if let Some(v) = directive_re.captures(text) {
+            let args: Vec<&str> = split_args(&v[2], false);
+            let data = match directive(&v[1].to_lowercase(), &args, location, (&mut chapter, &mut page), &mut constants, &used_pages) {
+                Ok(v) => v,
+                Err(e) => {
+                    diagnostics.push(e);
+                    continue;
+                },
+            };
+            for value in data {
+                used_pages.insert((chapter, page));
+                statements.push(Statement {
+                    location: location.clone(),
+                    chapter,
+                    page,
+                    mnemonic: ".BYTE".to_string(),
+                    operand: value,
+                    source: source_line.text.clone(),
+                });
+            }
+            continue;
+        }
+
+        let parts = match statement_re.captures(text) {
+            Some(v) => v,
+            None => {
+                diagnostics.push(error(location, format!("Could not parse \"{}\"", text)));
+                continue;
+            },
+        };
+        used_pages.insert((chapter, page));
+        statements.push(Statement {
+            location: location.clone(),
+            chapter,
+            page,
+            mnemonic: parts[1].to_uppercase(),
+            operand: match parts.get(2) {
+                Some(v) => operand(v.as_str()),
+                None => Operand::None,
+            },
+            source: source_line.text.clone(),
+        });
+    }
+    if diagnostics.is_empty() {
+        return Ok(Program { statements, labels, constants });
+    }
+    return Err(diagnostics);
+}
+
+//Whether a statement can leave status at 0 for the branch after it
+fn clears_status(statement : &Statement, version : ChipVariant) -> bool {
+    return match compiler::compile_instruction(&statement.mnemonic, 0, version) {
+        Some(v) => cfg::can_clear_status(v, version),
+        None => false,
+    };
+}
+
+//Statements reachable from a CALL target before its RETN, through fall-through and branches to labels
+fn subroutine_code(statements : &[Statement], entries : &HashMap<String, usize>, version : ChipVariant) -> HashSet<usize> {
+    let label_target = |statement : &Statement| match (statement.mnemonic.as_str(), &statement.operand) {
+        ("BR" | "CALL", Operand::Symbol(name)) => entries.get(name).copied(),
+        _ => None,
+    };
+    let mut pending: Vec<usize> = statements.iter().filter(|s| s.mnemonic == "CALL").filter_map(label_target).collect();
+    let mut reached: HashSet<usize> = HashSet::new();
+    while let Some(idx) = pending.pop() {
+        if !reached.insert(idx) {
+            continue;
+        }
+        let statement = &statements[idx];
+        let same_page = |other : usize| statements.get(other).is_some_and(|s| (s.chapter, s.page) == (statement.chapter, statement.page));
+        let conditional = idx > 0 && same_page(idx - 1) && clears_status(&statements[idx - 1], version);
+        if let Some(target) = label_target(statement) {
+            pending.push(target);
+        }
+        let falls_through = match statement.mnemonic.as_str() {
+            "RETN" => false,
+            "BR" => conditional,
+            _ => true,
+        };
+        if falls_through && same_page(idx + 1) {
+            pending.push(idx + 1);
+        }
+    }
+    return reached;
+}
+
+//Inserts an LDP before every BR/CALL whose target label is on another page, and checks that branches
+//into the other chapter follow a COMC and that subroutines stay on their page
+fn insert_page_loads(statements : Vec<Statement>, labels : &mut [Label], constants : &HashMap<String, usize>, version : ChipVariant) -> Result<Vec<Statement>, Diagnostic> {
+    let mut targets: HashMap<String, (usize, usize)> = HashMap::new(); //chapter, page
+    for label in labels.iter() {
+        if targets.contains_key(&label.name) {
+            return Err(error(&label.location, format!("Label {} defined more than once", label.name)));
+        }
+        if constants.contains_key(&label.name) {
+            return Err(error(&label.location, format!("Label {} is also defined as a constant", label.name)));
+        }
+        let target = match statements.get(label.statement) {
+            Some(v) => (v.chapter, v.page),
+            None => return Err(error(&label.location, format!("Label {} does not label an instruction", label.name))),
+        };
+        targets.insert(label.name.clone(), target);
+    }
+    let labelled: HashSet<usize> = labels.iter().map(|l| l.statement).collect();
+    let entries: HashMap<String, usize> = labels.iter().map(|l| (l.name.clone(), l.statement)).collect();
+    let subroutines = subroutine_code(&statements, &entries, version);
+
+    let mut results: Vec<Statement> = Vec::new();
+    let mut moved: Vec<usize> = Vec::with_capacity(statements.len()); //new index of each original statement
+    //Along the fall-through path since the last unconditional BR or RETN, or the start of the page
+    let mut comcs: usize = 0; //odd if CB holds the other chapter
+    let mut stale = false; //whether an inserted LDP may have left another page in the page buffer
+    let mut ended = true; //after an unconditional BR or RETN, only branches reach the next statement
+    for (idx, statement) in statements.into_iter().enumerate() {
+        let previous = results.last().filter(|p| (p.chapter, p.page) == (statement.chapter, statement.page));
+        if previous.is_none() || ended {
+            comcs = 0;
+            stale = false;
+        }
+        let conditional = previous.is_some_and(|p| clears_status(p, version));
+        ended = matches!(statement.mnemonic.as_str(), "BR" | "RETN") && !conditional;
+        if statement.mnemonic == "COMC" {
+            comcs += 1;
+        }
+        let target = match (statement.mnemonic.as_str(), &statement.operand) {
+            ("BR" | "CALL", Operand::Symbol(name)) => targets.get(name).map(|t| (name.clone(), *t)),
+            _ => None,
+        };
+        let (name, (chapter, page)) = match target {
+            Some(v) => v,
+            None => {
+                moved.push(results.len());
+                results.push(statement);
+                continue;
+            },
+        };
+        if version.is_TMS1100() && (chapter != statement.chapter) != (comcs % 2 == 1) {
+            return Err(error(&statement.location, match chapter != statement.chapter {
+                true => format!("{} to {} in chapter {} needs a COMC before it", statement.mnemonic, name, chapter),
+                false => format!("{} to {} follows an odd number of COMCs, so it would leave chapter {}", statement.mnemonic, name, chapter),
+            }));
+        }
+        if page != statement.page && subroutines.contains(&idx) {
+            return Err(error(&statement.location, format!("{} to {} on page {} is inside a subroutine, which can only branch within its own page", statement.mnemonic, name, page)));
+        }
+        if page == statement.page && !stale {
+            moved.push(results.len());
+            results.push(statement);
+            continue;
+        }
+        //Status only lasts one instruction, so the LDP of a conditional branch goes before the test
+        let load = Statement {
+            mnemonic: "LDP".to_string(),
+            operand: Operand::Number(page),
+            source: format!("LDP {} (inserted)", page),
+            ..statement.clone()
+        };
+        if conditional {
+            if labelled.contains(&idx) {
+                return Err(error(&statement.location, format!("{} to {} needs an LDP, but follows a status test and has a label, so no LDP can serve both paths", statement.mnemonic, name)));
+            }
+            //Labels on the test now label the LDP
+            results.insert(results.len() - 1, load);
+            moved.push(results.len());
+        }
+        else {
+            moved.push(results.len());
+            results.push(load);
+        }
+        results.push(statement);
+        //A branch that is not taken falls through with the target page still loaded
+        stale = conditional && page != results[results.len() - 1].page;
+    }
+    //Labels on a branch now label the inserted LDP, so that falling or branching into it still loads the page
+    for label in labels.iter_mut() {
+        label.statement = moved[label.statement];
+    }
+    return Ok(results);
+}
+
+//Assigns every statement its place in execution order on its page
+fn place(statements : &[Statement]) -> Result<Vec<Address>, Diagnostic> {
+    let mut next_index: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut addresses: Vec<Address> = Vec::with_capacity(statements.len());
+    for statement in statements {
+        let index = next_index.entry((statement.chapter, statement.page)).or_insert(0);
+        if *index >= 64 {
+            return Err(error(&statement.location, format!("Page {} of chapter {} is full (64 instructions)", statement.page, statement.chapter)));
+        }
+        addresses.push(Address { chapter: statement.chapter, page: statement.page, index: *index });
+        *index += 1;
+    }
+    return Ok(addresses);
+}
+
+fn encode(statement : &Statement, operand : usize, version : ChipVariant) -> Result<u8, Diagnostic> {
+    if statement.mnemonic == ".BYTE" {
+        return match u8::try_from(operand) {
+            Ok(v) => Ok(v),
+            Err(_) => Err(error(&statement.location, format!("Byte {} out of range (0-255)", operand))),
+        };
+    }
+    if compiler::compile_instruction(&statement.mnemonic, 0, version).is_none() {
+        return Err(error(&statement.location, format!("Unknown instruction {}", statement.mnemonic)));
+    }
+    let limit = compiler::operand_limit(&statement.mnemonic, version);
+    match (limit, &statement.operand) {
+        (None, Operand::None) => (),
+        (None, _) => return Err(error(&statement.location, format!("{} does not take an operand", statement.mnemonic))),
+        (Some(_), Operand::None) => return Err(error(&statement.location, format!("{} requires an operand", statement.mnemonic))),
+        (Some(max), _) => if operand > max as usize {
+            return Err(error(&statement.location, format!("Operand {} of {} out of range (0-{})", operand, statement.mnemonic, max)));
+        },
+    }
+    return match compiler::compile_instruction(&statement.mnemonic, operand as u8, version) {
+        Some(v) => Ok(v),
+        None => Err(error(&statement.location, format!("Unknown instruction {}", statement.mnemonic))),
+    };
+}
+
+fn assemble_lines(lines : &[SourceLine], version : ChipVariant) -> Result<Assembled, Vec<Diagnostic>> {
+    let Program { statements, mut labels, constants } = parse(lines)?;
+    let statements = insert_page_loads(statements, &mut labels, &constants, version).map_err(|e| vec![e])?;
+    let addresses = place(&statements).map_err(|e| vec![e])?;
+
+    let mut symbols: HashMap<String, Address> = HashMap::new();
+    for label in labels.iter() {
+        symbols.insert(label.name.clone(), addresses[label.statement]);
+    }
+
+    //Statements are independent once placed, so every bad one is reported
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let mut listing: Vec<ListingLine> = Vec::new();
+    let mut results: Vec<u8> = vec![0; version.rom_size()];
+    for (statement, address) in statements.iter().zip(addresses.iter()) {
+        let operand: usize = match &statement.operand {
+            Operand::None => 0,
+            Operand::Number(v) => *v,
+            Operand::Symbol(name) => match (statement.mnemonic.as_str(), symbols.get(name), constants.get(name)) {
+                ("BR" | "CALL", Some(target), _) => target.pc(),
+                (_, _, Some(value)) => *value,
+                (_, Some(_), None) => {
+                    diagnostics.push(error(&statement.location, format!("{} does not take a label", statement.mnemonic)));
+                    continue;
+                },
+                (_, None, None) => {
+                    diagnostics.push(error(&statement.location, undefined("symbol", name)));
+                    continue;
+                },
+            },
+        };
+        if address.rom_index() >= results.len() {
+            diagnostics.push(error(&statement.location, format!("Chapter {} does not exist on the {}", address.chapter, version)));
+            continue;
+        }
+        match encode(statement, operand, version) {
+            Ok(v) => {
+                results[address.rom_index()] = v;
+                listing.push(ListingLine {
+                    file: statement.location.file.clone(),
+                    line: statement.location.line,
+                    source: statement.source.clone(),
+                    chapter: address.chapter,
+                    page: address.page,
+                    pc: address.pc(),
+                    index: address.index,
+                    byte: v,
+                });
+            },
+            Err(e) => diagnostics.push(e),
+        }
+    }
+    if diagnostics.is_empty() {
+        let symbols: Vec<Symbol> = labels.iter().map(|label| {
+            let address = addresses[label.statement];
+            return Symbol { name: label.name.clone(), chapter: address.chapter, page: address.page, pc: address.pc() };
+        }).collect();
+        return Ok(Assembled { rom: results, listing, symbols });
+    }
+    return Err(diagnostics);
+}
+
+//Reads a file with its includes and expands macros
+fn preprocess_file(filename : &str) -> Result<Vec<SourceLine>, Diagnostic> {
+    let source = match fs::read_to_string(filename) {
+        Ok(v) => v,
+        Err(_) => return Err(Diagnostic { file: filename.to_string(), line: 0, column: 0, message: "Problem opening or reading file".to_string() }),
+    };
+    let path = Path::new(filename);
+    let mut lines: Vec<SourceLine> = Vec::new();
+    read_source(&source, filename, path.parent().unwrap_or(Path::new(".")), 0, &mut lines)?;
+    return expand_macros(lines);
+}
+
+//Assembles source text; included files are looked up relative to the working directory
+pub fn assemble(source : String, version : ChipVariant) -> Result<Vec<u8>, Vec<Diagnostic>> {
+    return assemble_with_listing(source, version).map(|v| v.rom);
+}
+
+//Assembles source text, also returning the listing and the address of every label
+pub fn assemble_with_listing(source : String, version : ChipVariant) -> Result<Assembled, Vec<Diagnostic>> {
+    let mut lines: Vec<SourceLine> = Vec::new();
+    read_source(&source, "<input>", Path::new("."), 0, &mut lines).map_err(|e| vec![e])?;
+    return assemble_lines(&expand_macros(lines).map_err(|e| vec![e])?, version);
+}
+
+//Assembles a file; included files are looked up relative to it
+pub fn assemble_file(filename : String, version : ChipVariant) -> Result<Assembled, Vec<Diagnostic>> {
+    return assemble_lines(&preprocess_file(&filename).map_err(|e| vec![e])?, version);
+}
+
+//The source of a file after includes and macros have been expanded, as it is fed to placement
+pub fn expand_file(filename : String) -> Result<String, Diagnostic> {
+    return Ok(listing_source(&preprocess_file(&filename)?));
+}
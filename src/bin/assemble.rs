@@ -0,0 +1,38 @@
+#![allow(clippy::unused_unit)]
+use tms::assembler;
+use tms::variant::ChipVariant;
+
+fn main() {
+    let version : ChipVariant = match std::env::args().nth(1).expect("No version number specified").parse() {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    let input_file = std::env::args().nth(2).expect("No input file given");
+    if std::env::args().nth(3).as_deref() == Some("--expand") {
+        match assembler::expand_file(input_file) {
+            Ok(v) => print!("{}", v),
+            Err(e) => println!("{}", e),
+        }
+        return ();
+    }
+    let output_file = match std::env::args().nth(3) {
+        Some(v) => v,
+        None => format!("{}.bin", input_file),
+    };
+    let rom = match assembler::assemble_file(input_file, version) {
+        Ok(v) => v,
+        Err(diagnostics) => {
+            for diagnostic in diagnostics.iter() {
+                println!("{}", diagnostic);
+            }
+            println!("{} errors, no output written", diagnostics.len());
+            return ();
+            },
+    };
+    match rom.write_files(&output_file, std::env::args().nth(4), std::env::args().nth(5)) {
+        Ok(_) => println!("Success!"),
+        Err(e) => println!("{}", e),
+    }
+}
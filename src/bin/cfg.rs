@@ -0,0 +1,37 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(unused_parens)]
+#![allow(clippy::unused_unit)]
+
+use tms::cfg;
+use tms::variant::ChipVariant;
+
+fn main() {
+    let version : ChipVariant = match std::env::args().nth(1).expect("No version number specified").parse() {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    let input_file = std::env::args().nth(2).expect("No input file given");
+    let rom = match std::fs::read(&input_file) {
+        Ok(v) => v,
+        Err(_) => {println!("Problem opening or reading {}", input_file);
+            return ();
+            },
+    };
+    let (rom, warning) = version.fit_rom(&rom);
+    //The graph may be going to standard output
+    if let Some(warning) = warning {
+        eprintln!("{}", warning);
+    }
+    let graph = cfg::build(&rom, version);
+    let dot = graph.to_dot(&rom, version);
+    match std::env::args().nth(3) {
+        Some(output_file) => match std::fs::write(&output_file, dot) {
+            Ok(_) => println!("{} basic blocks, {} edges written to {}", graph.blocks.len(), graph.edges.len(), output_file),
+            Err(_) => println!("An error occured"),
+        },
+        None => print!("{}", dot),
+    }
+}
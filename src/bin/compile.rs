@@ -1,29 +1,32 @@
+#![allow(clippy::unused_unit)]
 use tms::compiler;
-use std::fs;
+use tms::variant::ChipVariant;
 
 fn main() {
-    let version : u32 = std::env::args().nth(1).expect("No version number specified").parse().expect("Version number must be an integer");
+    let version : ChipVariant = match std::env::args().nth(1).expect("No version number specified").parse() {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
     let input_file = std::env::args().nth(2).expect("No input file given");
     let output_file = match std::env::args().nth(3) {
         Some(v) => v,
         None => format!("{}.out", input_file),
     };
-    let data: String =  match fs::read_to_string(&input_file) {
+    let rom = match compiler::compile_file(input_file, version) {
         Ok(v) => v,
-        Err(_) => panic!("Problem opening or reading input file"), //Nothing as permanant as a temporary solution
+        Err(diagnostics) => {
+            for diagnostic in diagnostics.iter() {
+                println!("{}", diagnostic);
+            }
+            println!("{} errors, no output written", diagnostics.len());
+            return ();
+            },
     };
-
-    if (version == 1100) || (version == 1300) {
-        match std::fs::write(output_file, compiler::compile_TMS1100(data)) {
-            Ok(_) => println!("Success!"),
-            _ => println!("An error occured"),
-        }
-    }
-    else {
-        match std::fs::write(output_file, compiler::compile_TMS1000(data)) {
-            Ok(_) => println!("Success!"),
-            _ => println!("An error occured"),
-        }
+    match rom.write_files(&output_file, std::env::args().nth(4), std::env::args().nth(5)) {
+        Ok(_) => println!("Success!"),
+        Err(e) => println!("{}", e),
     }
 
 }
@@ -1,16 +1,36 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 #![allow(unused_parens)]
+#![allow(clippy::unused_unit)]
 
+use tms::coverage::Coverage;
 use tms::decompiler;
+use tms::disassembler;
+use tms::variant::ChipVariant;
 
 fn main() {
-    let version : u32 = std::env::args().nth(1).expect("No version number specified").parse().expect("Version number must be an integer");
+    let version : ChipVariant = match std::env::args().nth(1).expect("No version number specified").parse() {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
     let input_file = std::env::args().nth(2).expect("No input file given");
-    if (version == 1100) || (version == 1300) {
-        decompiler::display_TMS1100(input_file);
+    if std::env::args().nth(3).as_deref() == Some("--disassemble") {
+        match std::fs::read(&input_file) {
+            Ok(rom) => print!("{}", disassembler::disassemble(&rom, version)),
+            Err(_) => println!("Problem opening or reading {}", input_file),
+        }
+        return ();
     }
-    else {
-        decompiler::display_TMS1000(input_file);
+    //Every further argument is a coverage file; they are merged and shown against the decompiled code
+    if std::env::args().nth(3).as_deref() == Some("--coverage") {
+        let files: Vec<String> = std::env::args().skip(4).collect();
+        match Coverage::read_all(&files) {
+            Ok(coverage) => print!("{}", coverage.overlay(&decompiler::decompile(input_file, version))),
+            Err(e) => println!("{}", e),
+        }
+        return ();
     }
+    decompiler::display(input_file, version);
 }
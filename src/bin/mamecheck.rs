@@ -0,0 +1,61 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(unused_parens)]
+#![allow(clippy::unused_unit)]
+
+use tms::TMS1000;
+use tms::mame;
+use tms::variant::ChipVariant;
+
+//mamecheck version rom ipla opla trace.log [k] [policy]
+fn main() {
+    let version : ChipVariant = match std::env::args().nth(1).expect("No version number specified").parse() {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    let ROM_file = std::env::args().nth(2).expect("No ROM file given");
+    let instruction_PLA = std::env::args().nth(3).expect("No instruction PLA given");
+    let output_PLA = std::env::args().nth(4).expect("No output PLA given");
+    let trace_file = std::env::args().nth(5).expect("No MAME trace given");
+    let k_inputs: u8 = match std::env::args().nth(6).unwrap_or("0".to_string()).parse() {
+        Ok(v) if v < 16 => v,
+        _ => {println!("K inputs must be 0-15");
+            return ();
+            },
+    };
+    //MAME starts with RAM and registers cleared
+    let policy : TMS1000::PowerOnPolicy = match std::env::args().nth(7).unwrap_or("zero".to_string()).parse() {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+
+    let records = match mame::read_mame_trace(&trace_file) {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    let mut system = match TMS1000::SYSTEM::load_system_with_policy(version, ROM_file, instruction_PLA, output_PLA, policy) {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    if let Some(warning) = system.get_rom_warning() {
+        println!("{}", warning);
+    }
+    system.set_logging();
+    match mame::check(&mut system, &records, k_inputs) {
+        Ok(v) => println!("All {} instructions match", v),
+        Err(e) => {
+            println!("Mismatch at {}", e);
+            if let Some(record) = records.get(e.index) {
+                println!("MAME: {:03X}: {}", record.rom_address, record.disassembly);
+            }
+        },
+    }
+}
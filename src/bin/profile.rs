@@ -0,0 +1,67 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(unused_parens)]
+#![allow(clippy::unused_unit)]
+
+use std::collections::HashMap;
+
+use tms::TMS1000;
+use tms::listing;
+use tms::variant::ChipVariant;
+
+fn main() {
+    let version : ChipVariant = match std::env::args().nth(1).expect("No version number specified").parse() {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    let ROM_file = std::env::args().nth(2).expect("No ROM file given");
+    let instruction_PLA = std::env::args().nth(3).expect("No instruction PLA given");
+    let output_PLA = std::env::args().nth(4).expect("No output PLA given");
+    let cycles: usize = match std::env::args().nth(5).unwrap_or("300000".to_string()).parse() {
+        Ok(v) => v,
+        Err(_) => {println!("Invalid number of cycles");
+            return ();
+            },
+    };
+    let k_inputs: u8 = match std::env::args().nth(6).unwrap_or("0".to_string()).parse() {
+        Ok(v) if v < 16 => v,
+        _ => {println!("K inputs must be 0-15");
+            return ();
+            },
+    };
+    let folded_file = std::env::args().nth(7);
+    let symbols: HashMap<usize, String> = match std::env::args().nth(8) {
+        Some(v) => match listing::read_symbols(&v) {
+            Ok(s) => s,
+            Err(e) => {println!("{}", e);
+                return ();
+                },
+        },
+        None => HashMap::new(),
+    };
+
+    let mut system = match TMS1000::SYSTEM::load_system(version, ROM_file, instruction_PLA, output_PLA) {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    if let Some(warning) = system.get_rom_warning() {
+        println!("{}", warning);
+    }
+    system.set_logging();
+    system.set_profiling(true);
+    for _ in 0..cycles {
+        system.instruction_cycle_mut(k_inputs);
+    }
+    let profile = system.get_profile().unwrap();
+    print!("{}", profile.report(&symbols, 40));
+    if let Some(v) = folded_file {
+        match std::fs::write(&v, profile.folded(&symbols)) {
+            Ok(_) => println!("\nFolded stacks written to {}", v),
+            Err(_) => println!("\nCould not write {}", v),
+        }
+    }
+}
@@ -0,0 +1,28 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(unused_parens)]
+#![allow(clippy::unused_unit)]
+
+use tms::reachability;
+use tms::variant::ChipVariant;
+
+fn main() {
+    let version : ChipVariant = match std::env::args().nth(1).expect("No version number specified").parse() {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    let input_file = std::env::args().nth(2).expect("No input file given");
+    let rom = match std::fs::read(&input_file) {
+        Ok(v) => v,
+        Err(_) => {println!("Problem opening or reading {}", input_file);
+            return ();
+            },
+    };
+    let (rom, warning) = version.fit_rom(&rom);
+    if let Some(warning) = warning {
+        println!("{}", warning);
+    }
+    print!("{}", reachability::report(&rom, version).format(&rom, version));
+}
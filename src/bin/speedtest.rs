@@ -1,8 +1,10 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 #![allow(unused_parens)]
+#![allow(clippy::unused_unit)]
 
 use tms::TMS1000;
+use tms::variant::ChipVariant;
 use std::time::SystemTime;
 
 
@@ -81,12 +83,26 @@ fn speedtest_strobek_500k(mut system : TMS1000::SYSTEM) {
 }
 
 fn main() {
-    let version : u32 = std::env::args().nth(1).expect("No version number specified").parse().expect("Version number must be an integer");
+    let version : ChipVariant = match std::env::args().nth(1).expect("No version number specified").parse() {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
     let ROM_file = std::env::args().nth(2).expect("No ROM file given");
     let instruction_PLA = std::env::args().nth(3).expect("No instruction PLA given");
     let output_PLA = std::env::args().nth(4).expect("No output PLA given");
+    let policy : TMS1000::PowerOnPolicy = match std::env::args().nth(5) {
+        Some(v) => match v.parse() {
+            Ok(p) => p,
+            Err(e) => {println!("{}", e);
+                return ();
+                },
+        },
+        None => TMS1000::PowerOnPolicy::Sentinel,
+    };
 
-    let system = match TMS1000::SYSTEM::load_system(version, ROM_file, instruction_PLA, output_PLA) {
+    let system = match TMS1000::SYSTEM::load_system_with_policy(version, ROM_file, instruction_PLA, output_PLA, policy) {
         Ok(v) => {println!("System loaded successfully");
             v
         },
@@ -94,6 +110,9 @@ fn main() {
             return ();
             },
     };
+    if let Some(warning) = system.get_rom_warning() {
+        println!("{}", warning);
+    }
     println!("K-inputs 0, 300000 instructions (1 second @ 300 khz)");
     speedtest_300k(system.clone(), 0);
     println!("K-inputs 1111, 300000 instructions (1 second @ 300 khz)");
@@ -1,22 +1,32 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 #![allow(unused_parens)]
+#![allow(clippy::needless_return)]
+#![allow(clippy::unused_unit)]
 
 use std::cmp;
 
 //pub mod TMS1000;
 use tms::TMS1000;
+use tms::variant::ChipVariant;
 use tms::decompiler;
+use tms::listing;
 use regex::Regex;
 use std::collections::HashMap;
 use std::fs::OpenOptions;
 use std::io::Write;
 
 fn input() -> String
+{
+    return input_line().to_lowercase();
+}
+
+//For file names, which keep their case
+fn input_line() -> String
 {
     let mut inp = String::new();
     std::io::stdin().read_line(&mut inp).expect("Could not read from stdin");
-    return inp.to_lowercase();
+    return inp;
 }
 
 
@@ -39,17 +49,49 @@ fn get_bin_input(max : u32) -> u32 {
     }
 }
 
+//Label at a position in execution order (as indexes decompiled_code), prefixed ready for printing
+fn label_at(symbols : &HashMap<usize, String>, exec_index : usize) -> String {
+    let rom_index = (exec_index & !63) + TMS1000::PC_SEQ[exec_index % 64];
+    return match symbols.get(&rom_index) {
+        Some(v) => format!("{}: ", v),
+        None => String::new(),
+    };
+}
+
 //This entire file was originally just a stopgap to test the TMS1000 emulator
 //Hence the very basic user interface and argument parsing
 //"There's nothing so permanant as a temporary solution"
 fn main() {
-    let version : u32 = std::env::args().nth(1).expect("No version number specified").parse().expect("Version number must be an integer");
+    let version : ChipVariant = match std::env::args().nth(1).expect("No version number specified").parse() {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
     let ROM_file = std::env::args().nth(2).expect("No ROM file given");
     let instruction_PLA = std::env::args().nth(3).expect("No instruction PLA given");
     let output_PLA = std::env::args().nth(4).expect("No output PLA given");
+    let policy : TMS1000::PowerOnPolicy = match std::env::args().nth(5) {
+        Some(v) => match v.parse() {
+            Ok(p) => p,
+            Err(e) => {println!("{}", e);
+                return ();
+                },
+        },
+        None => TMS1000::PowerOnPolicy::Sentinel,
+    };
+    let mut symbols: HashMap<usize, String> = match std::env::args().nth(6) {
+        Some(v) => match listing::read_symbols(&v) {
+            Ok(s) => s,
+            Err(e) => {println!("{}", e);
+                return ();
+                },
+        },
+        None => HashMap::new(),
+    };
     let decompiled_code = decompiler::decompile(ROM_file.clone(), version);
 
-    let mut system = match TMS1000::SYSTEM::load_system(version, ROM_file, instruction_PLA, output_PLA) {
+    let mut system = match TMS1000::SYSTEM::load_system_with_policy(version, ROM_file, instruction_PLA, output_PLA, policy) {
         Ok(v) => {println!("System loaded successfully");
             v
         },
@@ -57,6 +99,14 @@ fn main() {
             return ();
             },
     };
+    if let Some(warning) = system.get_rom_warning() {
+        println!("{}", warning);
+    }
+
+    system.set_definedness_tracking(true);
+    system.set_coverage(true);
+    let mut undefined_reads: Vec<TMS1000::UndefinedRead> = Vec::new();
+    let mut access_faults: Vec<TMS1000::AccessFault> = Vec::new();
 
     let mut prevcommand : String;
     let mut command : String = String::with_capacity(15);
@@ -76,12 +126,12 @@ fn main() {
 
     let mut auto_run = 0;
 
-    while !(command == "quit\n") {
+    while command != "quit\n" {
 
         k_inputs = match rtriggers.get(&system.get_r_outputs_u32()) {
             Some(v) => {
                 r_trigger_on = true;
-                v.clone()},
+                *v},
             _ => {
                 if r_trigger_on {
                     r_trigger_on = false;
@@ -96,7 +146,7 @@ fn main() {
         k_inputs = match otriggers.get(&system.get_o_outputs()) {
             Some(v) => {
                 o_trigger_on = true;
-                v.clone()},
+                *v},
             _ => {
                 if o_trigger_on {
                     o_trigger_on = false;
@@ -111,7 +161,7 @@ fn main() {
         println!("K inputs: {:0>4b}", k_inputs);
         println!("R outputs: {:0>16b}", system.get_r_outputs_u32());
         println!("O outputs: {:0>10b}", system.get_o_outputs());
-        println!("{}", format!("Next Instruction: {}", decompiled_code[system.get_rom_index()]));
+        println!("Next Instruction: {}{}", label_at(&symbols, system.get_rom_index()), decompiled_code[system.get_rom_index()]);
 
         prevcommand = command;
       //  command = String::with_capacity(15); //probably slightly better performance than cloning command into prevcommand
@@ -150,8 +200,8 @@ fn main() {
             "setk\n" | "sk\n" => k_inputs = get_bin_input(4) as u8,
             "seenext\n" | "next\n" | "sn\n" | "n\n" => {
                 let end: usize = cmp::min(system.get_rom_index() + 10, decompiled_code.len() - 1);
-                for line in decompiled_code[system.get_rom_index()..end].iter() {
-                    println!("{}", line);
+                for (i, line) in decompiled_code[system.get_rom_index()..end].iter().enumerate() {
+                    println!("{}{}", label_at(&symbols, system.get_rom_index() + i), line);
                 }
             },
             "setbreak\n" | "setb\n" | "sb\n" => {
@@ -187,7 +237,7 @@ fn main() {
                 }
                 else {
                     println!("Enter file to enter logs in");
-                    match OpenOptions::new().write(true).append(true).create(true).open(input().trim()) {
+                    match OpenOptions::new().append(true).create(true).open(input().trim()) {
                         Ok(f) => {
                             logout = true;
                             log_file = Some(f);
@@ -197,7 +247,71 @@ fn main() {
                     }
                 }
             },
+            "undefinedreads\n" | "undefined\n" | "ur\n" => {
+                for read in undefined_reads.iter() {
+                    let label = match symbols.get(&read.rom_address) {
+                        Some(v) => format!("{}: ", v),
+                        None => String::new(),
+                    };
+                    println!("{}{} {:0>2} {:0>2} ({}) : {}", label, read.chapter, read.page, read.pc, decompiler::decodeinstruction(read.instruction, version), read.source);
+                }
+                println!("{} reads of undefined values", undefined_reads.len());
+            },
+            "accesspolicy\n" | "ap\n" => {
+                println!("Access policy is {:?}. Enter panic, wrap or halt", system.get_access_policy());
+                match input_line().parse::<TMS1000::AccessPolicy>() {
+                    Ok(v) => system.set_access_policy(v),
+                    Err(e) => println!("{}", e),
+                }
+            },
+            "accessfaults\n" | "af\n" => {
+                for fault in access_faults.iter() {
+                    let label = match symbols.get(&fault.rom_address) {
+                        Some(v) => format!("{}: ", v),
+                        None => String::new(),
+                    };
+                    println!("{}{} {:0>2} {:0>2} ({}) : {}", label, fault.chapter, fault.page, fault.pc, decompiler::decodeinstruction(fault.instruction, version), fault.access);
+                }
+                if system.is_halted() {
+                    println!("The system is halted until initialized");
+                }
+                println!("{} out of range accesses", access_faults.len());
+            },
+            "loadsymbols\n" | "symbols\n" | "ls\n" => {
+                println!("Enter symbol file");
+                match listing::read_symbols(input_line().trim()) {
+                    Ok(v) => {
+                        println!("{} symbols loaded", v.len());
+                        symbols = v;
+                    },
+                    Err(e) => println!("{}", e),
+                }
+            },
+            "coverage\n" | "cov\n" => {
+                println!("Enter file to write coverage to");
+                match system.get_coverage() {
+                    Some(v) => match v.write(input_line().trim()) {
+                        Ok(_) => println!("Coverage written"),
+                        Err(e) => println!("{}", e),
+                    },
+                    None => println!("Coverage is not being recorded"),
+                }
+            },
+            "printsymbols\n" | "psym\n" => {
+                let mut sorted: Vec<(&usize, &String)> = symbols.iter().collect();
+                sorted.sort();
+                for (rom_index, name) in sorted {
+                    println!("{} {} {:0>2} {:0>2}", name, rom_index / 1024, (rom_index / 64) % 16, rom_index % 64);
+                }
+            },
             "init\n" | "initialize\n" | "reinitialize\n" => system.INITIALIZE(),
+            "holdinit\n" | "hi\n" => {
+                println!("Enter number of instruction cycles to hold INIT for");
+                match input().trim().parse::<u32>() {
+                    Ok(v) => system.set_init(v),
+                    Err(_) => println!("Invalid number"),
+                }
+            },
             "quit\n" | "q\n" => {println!("Goodbye");
                 command = "quit\n".into();},
             "auto100\n" | "a100\n" => {
@@ -218,8 +332,10 @@ fn main() {
             "auto10000000\n" | "a10000000\n" => {
                 auto_run = 1000000;
                 command = "cycle".into();},
-            _ => println!("Could not interpret command\nValid commands are: step, s, cycle, c, setk, sk, seenext, next, sn, setbreak, setb, sb, sethalt, seth, sh, printram, printr, pr, clearotriggers, clearotrigger, clot, cot, clearrtriggers, clearrtrigger, clrt, crt, setotrigger, setot, sot, setrtrigger, setrt, srt, settings, printsettings, ps, registers, printregisters, pn, setlog, logfile, logout, lo, reinitialize, initialize, init, quit, q, auto100, a100, auto1000, a1000, auto10000, a10000, auto100000, a100000, auto1000000, a1000000, auto10000000, a10000000"),
+            _ => println!("Could not interpret command\nValid commands are: step, s, cycle, c, setk, sk, seenext, next, sn, setbreak, setb, sb, sethalt, seth, sh, printram, printr, pr, clearotriggers, clearotrigger, clot, cot, clearrtriggers, clearrtrigger, clrt, crt, setotrigger, setot, sot, setrtrigger, setrt, srt, settings, printsettings, ps, registers, printregisters, pn, undefinedreads, undefined, ur, accesspolicy, ap, accessfaults, af, loadsymbols, symbols, ls, printsymbols, psym, coverage, cov, setlog, logfile, logout, lo, reinitialize, initialize, init, holdinit, hi, quit, q, auto100, a100, auto1000, a1000, auto10000, a10000, auto100000, a100000, auto1000000, a1000000, auto10000000, a10000000"),
         }
+        undefined_reads.append(&mut system.get_undefined_reads());
+        access_faults.append(&mut system.get_access_faults());
         let log = system.get_log();
         for entry in log.iter() {
             println!("{}", entry);
@@ -0,0 +1,101 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(unused_parens)]
+#![allow(clippy::unused_unit)]
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use tms::TMS1000;
+use tms::trace::{self, TraceRecord, TraceWriter};
+use tms::variant::ChipVariant;
+
+fn print_record(name : &str, record : &Option<TraceRecord>) {
+    match record {
+        Some(v) => println!("{}: {}", name, v),
+        None => println!("{}: (ended)", name),
+    }
+}
+
+//trace --diff first.trace second.trace
+fn diff(left_file : String, right_file : String) {
+    let (left, right) = match (trace::read_trace(&left_file), trace::read_trace(&right_file)) {
+        (Ok(l), Ok(r)) => (l, r),
+        (Err(e), _) | (_, Err(e)) => {println!("{}", e);
+            return ();
+            },
+    };
+    match trace::first_divergence(&left, &right) {
+        None => println!("Traces identical ({} cycles)", left.len()),
+        Some(d) => {
+            println!("{}", trace::HEADER);
+            if d.index > 0 {
+                print_record("both", &left.get(d.index - 1).cloned());
+            }
+            print_record(&left_file, &d.left);
+            print_record(&right_file, &d.right);
+            println!("First difference at record {}: {}", d.index, d.fields.join(", "));
+        },
+    }
+}
+
+fn main() {
+    if std::env::args().nth(1).as_deref() == Some("--diff") {
+        diff(std::env::args().nth(2).expect("No first trace given"), std::env::args().nth(3).expect("No second trace given"));
+        return ();
+    }
+    let version : ChipVariant = match std::env::args().nth(1).expect("No version number specified").parse() {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    let ROM_file = std::env::args().nth(2).expect("No ROM file given");
+    let instruction_PLA = std::env::args().nth(3).expect("No instruction PLA given");
+    let output_PLA = std::env::args().nth(4).expect("No output PLA given");
+    let cycles: u64 = match std::env::args().nth(5).unwrap_or("1000".to_string()).parse() {
+        Ok(v) => v,
+        Err(_) => {println!("Invalid number of cycles");
+            return ();
+            },
+    };
+    let k_inputs: u8 = match std::env::args().nth(6).unwrap_or("0".to_string()).parse() {
+        Ok(v) if v < 16 => v,
+        _ => {println!("K inputs must be 0-15");
+            return ();
+            },
+    };
+    let output: Box<dyn Write> = match std::env::args().nth(7) {
+        Some(v) => match File::create(&v) {
+            Ok(f) => Box::new(BufWriter::new(f)),
+            Err(_) => {println!("Could not create {}", v);
+                return ();
+                },
+        },
+        None => Box::new(BufWriter::new(std::io::stdout())),
+    };
+
+    let mut system = match TMS1000::SYSTEM::load_system(version, ROM_file, instruction_PLA, output_PLA) {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    //The trace itself may be going to standard output
+    if let Some(warning) = system.get_rom_warning() {
+        eprintln!("{}", warning);
+    }
+    system.set_logging();
+    let mut writer = match TraceWriter::new(output) {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    for cycle in 0..cycles {
+        if let Err(e) = writer.write(&TraceRecord::step(&mut system, version, cycle, k_inputs)) {
+            println!("{}", e);
+            return ();
+        }
+    }
+}
@@ -0,0 +1,88 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(unused_parens)]
+#![allow(clippy::needless_return)]
+#![allow(clippy::unused_unit)]
+
+use tms::TMS1000;
+use tms::trace;
+use tms::variant::ChipVariant;
+
+//"-" for a file of the second system means the same file as the first
+fn same_or(first : &str, v : String) -> String {
+    if v == "-" {
+        return first.to_string();
+    }
+    return v;
+}
+
+fn parse_policy(v : Option<String>, default : TMS1000::PowerOnPolicy) -> Result<TMS1000::PowerOnPolicy, String> {
+    return match v {
+        Some(p) => p.parse(),
+        None => Ok(default),
+    };
+}
+
+//tracediff version rom ipla opla rom2 ipla2 opla2 [cycles] [k] [policy] [policy2]
+fn main() {
+    let version : ChipVariant = match std::env::args().nth(1).expect("No version number specified").parse() {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    let ROM_file = std::env::args().nth(2).expect("No ROM file given");
+    let instruction_PLA = std::env::args().nth(3).expect("No instruction PLA given");
+    let output_PLA = std::env::args().nth(4).expect("No output PLA given");
+    let ROM_file_2 = same_or(&ROM_file, std::env::args().nth(5).expect("No second ROM file given"));
+    let instruction_PLA_2 = same_or(&instruction_PLA, std::env::args().nth(6).expect("No second instruction PLA given"));
+    let output_PLA_2 = same_or(&output_PLA, std::env::args().nth(7).expect("No second output PLA given"));
+    let cycles: u64 = match std::env::args().nth(8).unwrap_or("100000".to_string()).parse() {
+        Ok(v) => v,
+        Err(_) => {println!("Invalid number of cycles");
+            return ();
+            },
+    };
+    let k_inputs: u8 = match std::env::args().nth(9).unwrap_or("0".to_string()).parse() {
+        Ok(v) if v < 16 => v,
+        _ => {println!("K inputs must be 0-15");
+            return ();
+            },
+    };
+    let policy = match parse_policy(std::env::args().nth(10), TMS1000::PowerOnPolicy::Sentinel) {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+    let policy_2 = match parse_policy(std::env::args().nth(11), policy) {
+        Ok(v) => v,
+        Err(e) => {println!("{}", e);
+            return ();
+            },
+    };
+
+    let mut systems = Vec::new();
+    for (rom, ipla, opla, p) in [(ROM_file, instruction_PLA, output_PLA, policy), (ROM_file_2, instruction_PLA_2, output_PLA_2, policy_2)] {
+        match TMS1000::SYSTEM::load_system_with_policy(version, rom.clone(), ipla, opla, p) {
+            Ok(mut v) => {
+                if let Some(warning) = v.get_rom_warning() {
+                    println!("{}: {}", rom, warning);
+                }
+                v.set_logging();
+                systems.push(v);
+            },
+            Err(e) => {println!("{}", e);
+                return ();
+                },
+        }
+    }
+    let (left, right) = systems.split_at_mut(1);
+    match trace::lockstep(&mut left[0], &mut right[0], version, cycles, k_inputs) {
+        None => println!("No difference in {} cycles", cycles),
+        Some(d) => {
+            println!("First difference after cycle {}: {}\n", d.cycle, d.fields.join(", "));
+            print!("{}", d.left.side_by_side(&d.right));
+        },
+    }
+}
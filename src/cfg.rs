@@ -0,0 +1,291 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Static control-flow analysis
+//Walks a ROM from the reset vector (chapter 0, page 15, PC 0), following BR, CALL and RETN while
+//tracking the page and chapter buffers (LDP, COMC), the call latch and the subroutine return
+//registers, and groups what it reaches into basic blocks. Status is not known statically, so a BR or
+//CALL right after an instruction that can clear status is followed both ways.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::decompiler;
+use crate::variant::ChipVariant;
+use crate::TMS1000::{exec_index, format_address, PC_SEQ};
+
+//The registers that decide where execution goes next
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct FlowState {
+    chapter: usize, //CA
+    page: usize, //PA
+    pc: usize,
+    page_buffer: usize, //PB
+    chapter_buffer: usize, //CB
+    call_latch: bool, //CL
+    return_pc: usize, //SR
+    return_chapter: usize, //CSL
+    status_may_be_zero: bool, //set by the previous instruction
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EdgeKind {
+    FallThrough,
+    Branch, //BR taken
+    NotTaken, //BR or CALL with status 0
+    Call,
+    Return,
+}
+
+//Between ROM indexes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+//What tracing found
+#[derive(Clone, Debug, Default)]
+pub struct Flow {
+    pub reachable: Vec<bool>, //by ROM index
+    pub targets: BTreeMap<usize, BTreeSet<usize>>, //ROM index of a taken BR/CALL to the ROM indexes it lands on
+    pub subroutines: BTreeMap<usize, BTreeSet<usize>>, //entry point to the CALLs that reach it
+    pub branch_targets: BTreeSet<usize>, //destinations of BR
+    pub edges: BTreeSet<Edge>,
+}
+
+pub fn rom_index(chapter : usize, page : usize, pc : usize) -> usize {
+    return (1024 * chapter) + (64 * page) + pc;
+}
+
+fn mnemonic(instruction : u8, version : ChipVariant) -> String {
+    return decompiler::decodeinstruction(instruction, version).split_whitespace().next().unwrap_or("").to_string();
+}
+
+//Instructions using the standard PLA's NE or C8 microinstructions, which can leave status at 0
+pub fn can_clear_status(instruction : u8, version : ChipVariant) -> bool {
+    let name = mnemonic(instruction, version);
+    if version.is_TMS1100() && name.starts_with('A') && name.ends_with("AAC") && name != "AMAAC" {
+        return true;
+    }
+    return matches!(name.as_str(),
+        "A8AAC" | "A10AAC" | "A6AAC" | "YNEA" | "DAN" | "KNEZ" | "AMAAC" | "MNEZ" | "SAMAN" | "IMAC" | "ALEM" | "DMAN" | "IYC" | "DYN"
+        | "CPAIZ" | "TBIT1" | "YNEC" | "ALEC" | "MNEA" | "TAMDYN" | "TAMIYC");
+}
+
+//Every state that can follow an instruction
+fn successors(state : &FlowState, instruction : u8, version : ChipVariant) -> Vec<FlowState> {
+    let next_pc = PC_SEQ[(exec_index(state.pc) + 1) % 64];
+    let fall_through = FlowState { pc: next_pc, status_may_be_zero: can_clear_status(instruction, version), ..*state };
+    let mut results: Vec<FlowState> = Vec::new();
+    match instruction {
+        0x10..=0x1F => results.push(FlowState { page_buffer: (instruction.reverse_bits() >> 4) as usize, ..fall_through }),
+        0x0B if version.is_TMS1100() => results.push(FlowState { chapter_buffer: 1 - state.chapter_buffer, ..fall_through }),
+        0x0F => {
+            if state.call_latch {
+                results.push(FlowState { chapter: state.return_chapter, page: state.page_buffer, pc: state.return_pc, call_latch: false, ..fall_through });
+            }
+            else {
+                results.push(FlowState { page: state.page_buffer, ..fall_through });
+            }
+        },
+        0x80..=0xBF => {
+            let page = if state.call_latch { state.page } else { state.page_buffer };
+            results.push(FlowState { chapter: state.chapter_buffer, page, pc: (instruction % 64) as usize, ..fall_through });
+            if state.status_may_be_zero {
+                results.push(fall_through); //status returns to 1 after a branch fails
+            }
+        },
+        0xC0..=0xFF => {
+            let target = instruction % 64;
+            if state.call_latch {
+                results.push(FlowState { chapter: state.chapter_buffer, page_buffer: state.page, pc: target as usize, ..fall_through });
+            }
+            else {
+                results.push(FlowState {
+                    chapter: state.chapter_buffer,
+                    page: state.page_buffer,
+                    page_buffer: state.page,
+                    pc: target as usize,
+                    call_latch: true,
+                    return_pc: next_pc,
+                    return_chapter: state.chapter,
+                    ..fall_through
+                });
+            }
+            if state.status_may_be_zero {
+                results.push(fall_through);
+            }
+        },
+        _ => results.push(fall_through),
+    }
+    return results;
+}
+
+//Follows every path from the reset vector (chapter 0, page 15, PC 0)
+pub fn trace(rom : &[u8], version : ChipVariant) -> Flow {
+    let size = version.rom_size();
+    let mut flow = Flow { reachable: vec![false; size], ..Default::default() };
+    let start = FlowState { chapter: 0, page: 15, pc: 0, page_buffer: 15, chapter_buffer: 0, call_latch: false, return_pc: 0, return_chapter: 0, status_may_be_zero: false };
+    let mut seen: HashSet<FlowState> = HashSet::new();
+    let mut pending: Vec<FlowState> = vec![start];
+
+    while let Some(state) = pending.pop() {
+        if !seen.insert(state) {
+            continue;
+        }
+        let index = rom_index(state.chapter, state.page, state.pc);
+        if index >= size {
+            continue;
+        }
+        flow.reachable[index] = true;
+        let instruction = *rom.get(index).unwrap_or(&0);
+        for (taken, next) in successors(&state, instruction, version).into_iter().enumerate().map(|(i, s)| (i == 0, s)) {
+            let next_index = rom_index(next.chapter, next.page, next.pc);
+            let kind = match (instruction, taken) {
+                (0x80..=0xFF, false) => EdgeKind::NotTaken,
+                (0x80..=0xBF, true) => EdgeKind::Branch,
+                (0xC0..=0xFF, true) => EdgeKind::Call,
+                (0x0F, _) => EdgeKind::Return,
+                _ => EdgeKind::FallThrough,
+            };
+            if next_index < size {
+                flow.edges.insert(Edge { from: index, to: next_index, kind });
+            }
+            if instruction >= 0x80 && taken { //successors puts the branch first
+                flow.targets.entry(index).or_default().insert(next_index);
+                if instruction >= 0xC0 {
+                    flow.subroutines.entry(next_index).or_default().insert(index);
+                }
+                else {
+                    flow.branch_targets.insert(next_index);
+                }
+            }
+            pending.push(next);
+        }
+    }
+    return flow;
+}
+
+impl Flow {
+    //Name of the label at a ROM index, if anything branches or calls there
+    pub fn label(&self, index : usize) -> Option<String> {
+        let (chapter, page, pc) = (index / 1024, (index / 64) % 16, index % 64);
+        if self.subroutines.contains_key(&index) {
+            return Some(format!("S_{}_{:0>2}_{:0>2}", chapter, page, pc));
+        }
+        if self.branch_targets.contains(&index) {
+            return Some(format!("L_{}_{:0>2}_{:0>2}", chapter, page, pc));
+        }
+        return None;
+    }
+}
+
+
+//Instructions run one after another, entered only at the first and left only after the last
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BasicBlock {
+    pub instructions: Vec<usize>, //ROM indexes, in execution order
+}
+
+impl BasicBlock {
+    pub fn start(&self) -> usize {
+        return self.instructions[0];
+    }
+
+    pub fn end(&self) -> usize {
+        return self.instructions[self.instructions.len() - 1];
+    }
+}
+
+pub struct ControlFlowGraph {
+    pub flow: Flow,
+    pub blocks: Vec<BasicBlock>, //ordered by start address
+    pub edges: BTreeSet<Edge>, //between block starts
+}
+
+fn next_in_page(index : usize) -> usize {
+    return (index & !63) + PC_SEQ[(exec_index(index % 64) + 1) % 64];
+}
+
+pub fn build(rom : &[u8], version : ChipVariant) -> ControlFlowGraph {
+    let flow = trace(rom, version);
+    let mut outgoing: BTreeMap<usize, Vec<Edge>> = BTreeMap::new();
+    for edge in flow.edges.iter() {
+        outgoing.entry(edge.from).or_default().push(*edge);
+    }
+    let plain = |index : usize| -> bool {
+        return match outgoing.get(&index) {
+            Some(edges) => edges.iter().all(|e| e.kind == EdgeKind::FallThrough && e.to == next_in_page(index)),
+            None => false,
+        };
+    };
+
+    //A block starts at the reset vector, at anything reached other than by falling through, and after any instruction that does more than fall through
+    let mut leaders: BTreeSet<usize> = BTreeSet::new();
+    leaders.insert(rom_index(0, 15, 0));
+    for edge in flow.edges.iter() {
+        if !plain(edge.from) || edge.kind != EdgeKind::FallThrough || edge.to != next_in_page(edge.from) {
+            leaders.insert(edge.to);
+        }
+    }
+
+    let mut blocks: Vec<BasicBlock> = Vec::new();
+    let mut block_of: BTreeMap<usize, usize> = BTreeMap::new(); //block start of every instruction
+    for &leader in leaders.iter() {
+        let mut instructions: Vec<usize> = vec![leader];
+        let mut current = leader;
+        while plain(current) && instructions.len() < 64 {
+            let next = next_in_page(current);
+            if leaders.contains(&next) || !flow.reachable[next] {
+                break;
+            }
+            instructions.push(next);
+            current = next;
+        }
+        for &i in instructions.iter() {
+            block_of.insert(i, leader);
+        }
+        blocks.push(BasicBlock { instructions });
+    }
+
+    let mut edges: BTreeSet<Edge> = BTreeSet::new();
+    for block in blocks.iter() {
+        for edge in outgoing.get(&block.end()).into_iter().flatten() {
+            if let Some(&to) = block_of.get(&edge.to) {
+                edges.insert(Edge { from: block.start(), to, kind: edge.kind });
+            }
+        }
+    }
+    return ControlFlowGraph { flow, blocks, edges };
+}
+
+impl ControlFlowGraph {
+    //Graphviz source, one box per basic block listing its instructions
+    pub fn to_dot(&self, rom : &[u8], version : ChipVariant) -> String {
+        let mut results = String::from("digraph rom {\n    node [shape=box, fontname=\"monospace\"];\n");
+        for block in self.blocks.iter() {
+            let mut text = match self.flow.label(block.start()) {
+                Some(name) => format!("{}\\l", name),
+                None => String::new(),
+            };
+            for &index in block.instructions.iter() {
+                let instruction = decompiler::decodeinstruction(*rom.get(index).unwrap_or(&0), version);
+                text.push_str(&format!("{} : {}\\l", format_address(index), instruction));
+            }
+            results.push_str(&format!("    b{} [label=\"{}\"];\n", block.start(), text));
+        }
+        for edge in self.edges.iter() {
+            let style = match edge.kind {
+                EdgeKind::FallThrough => "",
+                EdgeKind::Branch => " [label=\"BR\"]",
+                EdgeKind::NotTaken => " [style=dashed]",
+                EdgeKind::Call => " [label=\"CALL\", color=blue]",
+                EdgeKind::Return => " [style=dotted]",
+            };
+            results.push_str(&format!("    b{} -> b{}{};\n", edge.from, edge.to, style));
+        }
+        results.push_str("}\n");
+        return results;
+    }
+}
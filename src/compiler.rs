@@ -2,9 +2,17 @@
 #![allow(non_snake_case)]
 #![allow(unused_parens)]
 #![allow(non_upper_case_globals)]
+#![allow(clippy::needless_return)]
 
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
 use regex::Regex;
 
+use crate::variant::ChipVariant;
+use crate::listing::{Assembled, ListingLine, Symbol};
+use crate::TMS1000::exec_index;
+
 fn reversebits_u4(value : u8) -> u8 {
     return value.reverse_bits() >> 4;
 }
@@ -110,50 +118,235 @@ pub fn compile_instruction_TMS1100(instruction : String, operand : u8) -> Option
         "BR" => Some(0x80 + operand),
         "CALL" => Some(0xC0 + operand),
         _ => {
-            let add_const_re = Regex::new(r"^A([0-9]{1,2})AAC$").unwrap();
-            if let Some(v) = add_const_re.captures(instruction.as_str()) {
-                if let Some(op_str) = v.get(1) {
-                    if let Ok(op) = op_str.as_str().parse::<u8>() {
+            //A1AAC to A15AAC, without building a regex for every instruction compiled
+            if let Some(op_str) = instruction.strip_prefix('A').and_then(|v| v.strip_suffix("AAC")) {
+                if let Ok(op) = op_str.parse::<u8>() {
+                    if (1..=15).contains(&op) && op_str.len() <= 2 { //A16AAC would collide with CLA
                         return Some(0x70 + reversebits_u4(op - 1))
-                    };}}
+                    }
+                }
+            }
             None
         }
     }
 }
 
-pub fn compile_TMS1000(input : String) -> [u8; 64 * 16] {
-    let mut results: [u8; 64 * 16] = [0; 1024];
-    let asm_regex = Regex::new(r"([0-9]{1,2}) ([0-9]{1,2}) : ([0-9A-Z]{1,8})( [0-9]{1,2})?").unwrap();
-    for line in asm_regex.captures_iter(&input) {
-        let error: String = format!("Compiler: Error in line {:?}", line);
-        let page: usize = usize::from_str_radix(line[1].as_ref(), 10).expect(&error);
-        let word: usize = usize::from_str_radix(line[2].as_ref(), 10).expect(&error);
-        let instruction : String = line[3].to_string();
-        let operand: u8 = match line.get(4).is_none() {
-            false => u8::from_str_radix(line[4].trim().as_ref(), 10).unwrap_or(0),
-            _ => 0,
-        };
-        println!("{} {}", instruction, operand);
-        results[(page * 64) + word] = compile_instruction_TMS1000(instruction, operand).expect(&error);
+//Numbers may be decimal, hexadecimal ("0x1F", "$1F"), binary ("0b1010", "%1010") or a character constant ('A')
+pub const NUMBER_PATTERN : &str = r"(?:0[xX][0-9A-Fa-f]+|\$[0-9A-Fa-f]+|0[bB][01]+|%[01]+|'[^']'|[0-9]+)";
+
+pub fn parse_number(token : &str) -> Option<usize> {
+    let token = token.trim();
+    //from_str_radix takes a sign, so the digits are checked first
+    if let Some(v) = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")).or_else(|| token.strip_prefix('$')) {
+        if !v.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        return usize::from_str_radix(v, 16).ok();
+    }
+    if let Some(v) = token.strip_prefix("0b").or_else(|| token.strip_prefix("0B")).or_else(|| token.strip_prefix('%')) {
+        if !v.chars().all(|c| c == '0' || c == '1') {
+            return None;
+        }
+        return usize::from_str_radix(v, 2).ok();
+    }
+    let chars: Vec<char> = token.chars().collect();
+    if chars.len() == 3 && chars[0] == '\'' && chars[2] == '\'' {
+        return Some(chars[1] as usize);
+    }
+    if !token.is_empty() && token.chars().all(|c| c.is_ascii_digit()) { //no sign, unlike str::parse
+        return token.parse::<usize>().ok();
+    }
+    return None;
+}
+
+//Largest operand accepted by an instruction, or None if it takes no operand
+pub fn operand_limit(instruction : &str, version : ChipVariant) -> Option<u8> {
+    return match instruction {
+        "LDP" | "TCY" | "YNEC" | "TCMIY" => Some(15),
+        "ALEC" if !version.is_TMS1100() => Some(15),
+        "SBIT" | "RBIT" | "TBIT1" => Some(3),
+        "LDX" => if version.is_TMS1100() { Some(7) } else { Some(3) },
+        "BR" | "CALL" => Some(63),
+        ".BYTE" => Some(255), //raw data
+        _ => None,
+    };
+}
+
+//Where a line of source is wrong, and why
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}:{}:{}: {}", self.file, self.line, self.column, self.message);
+    }
+}
+
+impl Diagnostic {
+    pub fn new(line : usize, column : usize, message : String) -> Self {
+        return Diagnostic { file: "<input>".to_string(), line, column, message };
     }
-    return results;
-}
-
-pub fn compile_TMS1100(input: String) -> [u8; 64 * 16 * 2] {
-    let mut results: [u8; 64 * 16 * 2] = [0; 2048];
-    let asm_regex = Regex::new(r"([0-9]) ([0-9]{1,2}) ([0-9]{1,2}) : ([0-9A-Z]{1,8})( [0-9]{1,2})?").unwrap();
-    for line in asm_regex.captures_iter(&input) {
-        let error: String = format!("Compiler: Error in line {:?}", line);
-        let chapter: usize = usize::from_str_radix(line[1].as_ref(), 10).expect(&error);
-        let page: usize = usize::from_str_radix(line[2].as_ref(), 10).expect(&error);
-        let word: usize = usize::from_str_radix(line[3].as_ref(), 10).expect(&error);
-        let instruction : String= line[4].to_string();
-        let operand: u8 = match line.get(5).is_none() {
-            false => u8::from_str_radix(line[5].trim().as_ref(), 10).unwrap_or(0),
-            _ => 0,
+}
+
+//Parses decompiler style lines ("[chapter] page pc : MNEMONIC [operand]") into the ROM image
+//A line may start with "label:", naming the next instruction in the symbol output
+//Every problem found is reported, rather than stopping at the first
+fn compile_lines(input : &str, version : ChipVariant) -> Result<Assembled, Vec<Diagnostic>> {
+    let chapters: usize = if version.is_TMS1100() { 2 } else { 1 };
+    //The decompiler's line format (see decompiler::format_line), optionally preceded by the "idx - " of display()
+    //The chapter may be left out on single chapter ROMs, and the (EE) execution order annotations are checked if present
+    let asm_regex = Regex::new(&format!(r"^\s*(?:{n}\s+-\s+)?(?:({n})\s+)?({n})\s+({n})\s*(?:\(({n})\))?\s*:\s*(\.BYTE|[0-9A-Z]{{1,8}})(?:\s+({n}))?\s*(?:\(({n})\))?\s*(?:;.*)?$", n = NUMBER_PATTERN)).unwrap();
+    //Blank lines, comments, and display() lines for bytes beyond the end of the ROM
+    let blank_regex = Regex::new(r"^\s*([0-9]+ -)?\s*(;.*)?$").unwrap();
+    let label_regex = Regex::new(r"^\s*([A-Za-z_][A-Za-z0-9_]*):").unwrap();
+
+    let mut results: Vec<u8> = vec![0; 1024 * chapters];
+    let mut assigned: HashMap<usize, usize> = HashMap::new(); //ROM index to the line that set it
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let mut listing: Vec<ListingLine> = Vec::new();
+    let mut symbols: Vec<Symbol> = Vec::new();
+    let mut labels: Vec<String> = Vec::new(); //waiting for the next instruction
+
+    for (idx, full_text) in input.lines().enumerate() {
+        let line_number = idx + 1;
+        let text = match label_regex.captures(full_text) {
+            Some(v) => {
+                labels.push(v[1].to_string());
+                &full_text[v[0].len()..]
+            },
+            None => full_text,
+        };
+        let offset = full_text.len() - text.len(); //columns are reported against the full line
+        let line = match asm_regex.captures(text) {
+            Some(v) => v,
+            None => {
+                if !blank_regex.is_match(text) {
+                    diagnostics.push(Diagnostic::new(line_number, offset + 1, format!("Could not parse \"{}\"", text.trim())));
+                }
+                continue;
+            },
         };
-        println!("{} {}", instruction, operand);
-        results[(chapter * 1024) + (page * 64) + word] = compile_instruction_TMS1100(instruction, operand).expect(&error);
+        let column = |group : usize| -> usize { offset + line.get(group).map(|m| m.start() + 1).unwrap_or(1) };
+        let address_column = column(1).min(column(2));
+        //Numbers are guarenteed by the regex, short of overflowing
+        let number = |group : usize| -> Option<usize> { line.get(group).map(|v| parse_number(v.as_str()).unwrap_or(usize::MAX)) };
+        let chapter: usize = number(1).unwrap_or(0);
+        let page: usize = number(2).unwrap_or(usize::MAX);
+        let word: usize = number(3).unwrap_or(usize::MAX);
+        let instruction : String = line[5].to_string();
+        let operand: Option<usize> = number(6);
+
+        if chapter >= chapters || page > 15 || word > 63 {
+            diagnostics.push(Diagnostic::new(line_number, address_column, format!("Address {} {} {} out of range", chapter, page, word)));
+            continue;
+        }
+        if let Some(v) = number(4) {
+            if v != exec_index(word) {
+                diagnostics.push(Diagnostic::new(line_number, column(4), format!("PC {} is execution index {}, not {}", word, exec_index(word), v)));
+                continue;
+            }
+        }
+        if let (Some(v), Some(target)) = (number(7), operand) {
+            if target > 63 || v != exec_index(target) {
+                diagnostics.push(Diagnostic::new(line_number, column(7), format!("Annotation ({}) does not match the execution index of {}", v, target)));
+                continue;
+            }
+        }
+        let index = (chapter * 1024) + (page * 64) + word;
+        if let Some(previous) = assigned.get(&index) {
+            diagnostics.push(Diagnostic::new(line_number, address_column, format!("Address {} {} {} already assigned on line {}", chapter, page, word, previous)));
+            continue;
+        }
+        assigned.insert(index, line_number);
+        for name in labels.drain(..) {
+            symbols.push(Symbol { name, chapter, page, pc: word });
+        }
+
+        match (operand_limit(&instruction, version), operand) {
+            (Some(_), None) => {
+                diagnostics.push(Diagnostic::new(line_number, column(5), format!("{} requires an operand", instruction)));
+                continue;
+            },
+            (Some(max), Some(v)) if v > max as usize => {
+                diagnostics.push(Diagnostic::new(line_number, column(6), format!("Operand {} of {} out of range (0-{})", v, instruction, max)));
+                continue;
+            },
+            (None, Some(v)) if compile_instruction(&instruction, 0, version).is_some() => {
+                diagnostics.push(Diagnostic::new(line_number, column(6), format!("{} does not take an operand (got {})", instruction, v)));
+                continue;
+            },
+            _ => (),
+        }
+        let encoded = match instruction.as_str() {
+            ".BYTE" => operand.map(|v| v as u8),
+            _ => compile_instruction(&instruction, operand.unwrap_or(0) as u8, version),
+        };
+        match encoded {
+            Some(v) => {
+                results[index] = v;
+                listing.push(ListingLine {
+                    file: "<input>".to_string(),
+                    line: line_number,
+                    source: full_text.trim().to_string(),
+                    chapter,
+                    page,
+                    pc: word,
+                    index: exec_index(word),
+                    byte: v,
+                });
+            },
+            None => diagnostics.push(Diagnostic::new(line_number, column(5), format!("Unknown instruction {}", instruction))),
+        }
+    }
+    if diagnostics.is_empty() {
+        return Ok(Assembled { rom: results, listing, symbols });
+    }
+    return Err(diagnostics);
+}
+
+pub fn compile_instruction(instruction : &str, operand : u8, version : ChipVariant) -> Option<u8> {
+    return match version.is_TMS1100() {
+        true => compile_instruction_TMS1100(instruction.to_string(), operand),
+        false => compile_instruction_TMS1000(instruction.to_string(), operand),
+    };
+}
+
+pub fn compile_TMS1000(input : String) -> Result<[u8; 64 * 16], Vec<Diagnostic>> {
+    let results = compile_lines(&input, ChipVariant::TMS1000)?;
+    return Ok(results.rom.try_into().unwrap()); //compile_lines sizes the ROM by chapter count
+}
+
+pub fn compile_TMS1100(input: String) -> Result<[u8; 64 * 16 * 2], Vec<Diagnostic>> {
+    let results = compile_lines(&input, ChipVariant::TMS1100)?;
+    return Ok(results.rom.try_into().unwrap());
+}
+
+pub fn compile(input : String, version : ChipVariant) -> Result<Vec<u8>, Vec<Diagnostic>> {
+    return compile_with_listing(input, version).map(|v| v.rom);
+}
+
+//Compiles, also returning the listing and the labels defined in the source
+pub fn compile_with_listing(input : String, version : ChipVariant) -> Result<Assembled, Vec<Diagnostic>> {
+    return compile_lines(&input, version);
+}
+
+//Compiles a file, naming it in any diagnostics and in the listing
+pub fn compile_file(filename : String, version : ChipVariant) -> Result<Assembled, Vec<Diagnostic>> {
+    let data: String = match fs::read_to_string(&filename) {
+        Ok(v) => v,
+        Err(_) => return Err(vec![Diagnostic { file: filename, line: 0, column: 0, message: "Problem opening or reading file".to_string() }]),
+    };
+    let mut results = match compile_with_listing(data, version) {
+        Ok(v) => v,
+        Err(diagnostics) => return Err(diagnostics.into_iter().map(|d| Diagnostic { file: filename.clone(), ..d }).collect()),
+    };
+    for entry in results.listing.iter_mut() {
+        entry.file = filename.clone();
     }
-    return results;
+    return Ok(results);
 }
@@ -0,0 +1,126 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Code coverage
+//Recorded by SYSTEM once enabled with set_coverage: how often each ROM byte was fetched for
+//execution, and how often each BR/CALL was taken and not taken. Coverage files are plain text,
+//one line per byte that was reached:
+//    C PP WW executions taken not_taken
+//and merge by adding the counts, so several scripted runs can be combined.
+
+use std::fs;
+
+use crate::TMS1000::PC_SEQ;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Coverage {
+    pub executions: Vec<u64>, //by ROM index
+    pub taken: Vec<u64>,
+    pub not_taken: Vec<u64>,
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        return Coverage::new();
+    }
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        return Coverage { executions: vec![0; 2048], taken: vec![0; 2048], not_taken: vec![0; 2048] };
+    }
+
+    pub(crate) fn record_execution(&mut self, rom_index : usize) {
+        if let Some(v) = self.executions.get_mut(rom_index) {
+            *v += 1;
+        }
+    }
+
+    pub(crate) fn record_branch(&mut self, rom_index : usize, taken : bool) {
+        let counts = if taken { &mut self.taken } else { &mut self.not_taken };
+        if let Some(v) = counts.get_mut(rom_index) {
+            *v += 1;
+        }
+    }
+
+    pub fn merge(&mut self, other : &Coverage) {
+        for (ours, theirs) in [(&mut self.executions, &other.executions), (&mut self.taken, &other.taken), (&mut self.not_taken, &other.not_taken)] {
+            for (a, b) in ours.iter_mut().zip(theirs.iter()) {
+                *a += b;
+            }
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut results = String::new();
+        for index in 0..self.executions.len() {
+            if self.executions[index] + self.taken[index] + self.not_taken[index] > 0 {
+                results.push_str(&format!("{} {:0>2} {:0>2} {} {} {}\n", index / 1024, (index / 64) % 16, index % 64, self.executions[index], self.taken[index], self.not_taken[index]));
+            }
+        }
+        return results;
+    }
+
+    pub fn from_text(data : &str) -> Result<Coverage, String> {
+        let mut results = Coverage::new();
+        for (idx, line) in data.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<u64> = match line.split_whitespace().map(|f| f.parse::<u64>()).collect() {
+                Ok(v) => v,
+                Err(_) => return Err(format!("Coverage: line {}: could not parse \"{}\"", idx + 1, line.trim())),
+            };
+            if fields.len() != 6 || fields[0] > 1 || fields[1] > 15 || fields[2] > 63 {
+                return Err(format!("Coverage: line {}: could not parse \"{}\"", idx + 1, line.trim()));
+            }
+            let index = (1024 * fields[0] + 64 * fields[1] + fields[2]) as usize;
+            results.executions[index] += fields[3];
+            results.taken[index] += fields[4];
+            results.not_taken[index] += fields[5];
+        }
+        return Ok(results);
+    }
+
+    pub fn write(&self, filename : &str) -> Result<(), String> {
+        return fs::write(filename, self.to_text()).map_err(|_| format!("Coverage: could not write {}", filename));
+    }
+
+    pub fn read(filename : &str) -> Result<Coverage, String> {
+        return match fs::read_to_string(filename) {
+            Ok(v) => Coverage::from_text(&v),
+            Err(_) => Err(format!("Coverage: Problem opening or reading {}", filename)),
+        };
+    }
+
+    //Reads and merges several coverage files
+    pub fn read_all(filenames : &[String]) -> Result<Coverage, String> {
+        let mut results = Coverage::new();
+        for filename in filenames.iter() {
+            results.merge(&Coverage::read(filename)?);
+        }
+        return Ok(results);
+    }
+
+    //Prefixes decompiler::decompile lines (indexed by execution order) with their counts
+    //Bytes never executed are marked with #####, as gcov does
+    pub fn overlay(&self, decompiled : &[String]) -> String {
+        let mut results = String::new();
+        for (idx, line) in decompiled.iter().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let index = (idx & !63) + PC_SEQ[idx % 64];
+            let count = match self.executions[index] {
+                0 => "#####".to_string(),
+                v => v.to_string(),
+            };
+            let branches = match (self.taken[index], self.not_taken[index]) {
+                (0, 0) => String::new(),
+                (t, n) => format!("  [taken {}, not taken {}]", t, n),
+            };
+            results.push_str(&format!("{:>10} | {}{}\n", count, line, branches));
+        }
+        return results;
+    }
+}
@@ -1,9 +1,12 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 #![allow(unused_parens)]
+#![allow(clippy::needless_return)]
 
 use std::fs::File;
 use std::io::Read;
+
+use crate::variant::ChipVariant;
 //use std::str;
 
 const PC_SEQ: [u8; 64] = [0x00, 0x01, 0x03, 0x07, 0x0F, 0x1F, 0x3F, 0x3E, 0x3D, 0x3B, 0x37, 0x2F, 0x1E, 0x3C, 0x39, 0x33, 0x27, 0x0E, 0x1D, 0x3A, 0x35, 0x2B, 0x16, 0x2C, 0x18, 0x30, 0x21, 0x02, 0x05, 0x0B, 0x17, 0x2E, 0x1C, 0x38, 0x31, 0x23, 0x06, 0x0D, 0x1B, 0x36, 0x2D, 0x1A, 0x34, 0x29, 0x12, 0x24, 0x08, 0x11, 0x22, 0x04, 0x09, 0x13, 0x26, 0x0C, 0x19, 0x32, 0x25, 0x0A, 0x15, 0x2A, 0x14, 0x28, 0x10, 0x20];
@@ -42,7 +45,7 @@ fn decodeinstruction_TMS1000(instruction : u8) -> String{
         0x0D => return String::from("SETR"),
         0x0E => return String::from("IA"),
         0x0F => return String::from("RETN"),
-        0x10..=0x1F => return String::from("LDP ".to_owned() + &(reversebits_u4(instruction)).to_string()),
+        0x10..=0x1F => return format!("LDP {}", reversebits_u4(instruction)),
         0x20 => return String::from("TAMIY"),
         0x21 => return String::from("TMA"),
         0x22 => return String::from("TMY"),
@@ -59,16 +62,16 @@ fn decodeinstruction_TMS1000(instruction : u8) -> String{
         0x2D => return String::from("CPAIZ"),
         0x2E => return String::from("XMA"),
         0x2F => return String::from("CLA"),
-        0x30..=0x33 => return String::from("SBIT ".to_owned() + &(reversebits_u2(instruction)).to_string()),
-        0x34..=0x37 => return String::from("RBIT ".to_owned() + &(reversebits_u2(instruction)).to_string()),
-        0x38..=0x3B => return String::from("TBIT1 ".to_owned() + &(reversebits_u2(instruction)).to_string()), //check and fix
-        0x3C..=0x3F => return String::from("LDX ".to_owned() + &(reversebits_u2(instruction)).to_string()),
-        0x40..=0x4F => return String::from("TCY ".to_owned() + &(reversebits_u4(instruction)).to_string()),
-        0x50..=0x5F => return String::from("YNEC ".to_owned() + &(reversebits_u4(instruction)).to_string()),
-        0x60..=0x6F => return String::from("TCMIY ".to_owned() + &(reversebits_u4(instruction)).to_string()),
-        0x70..=0x7F => return String::from("ALEC ".to_owned() + &(reversebits_u4(instruction)).to_string()),
-        0x80..=0xBF => return String::from("BR ".to_owned() + &(instruction % 64).to_string() + " (" + &(PC_SEQ.iter().position(|&i| i == (instruction % 64)).unwrap()).to_string() + ")"),
-        0xC0..=0xFF => return String::from("CALL ".to_owned() + &(instruction % 64).to_string() + " (" + &(PC_SEQ.iter().position(|&i| i == (instruction % 64)).unwrap()).to_string() + ")"),
+        0x30..=0x33 => return format!("SBIT {}", reversebits_u2(instruction)),
+        0x34..=0x37 => return format!("RBIT {}", reversebits_u2(instruction)),
+        0x38..=0x3B => return format!("TBIT1 {}", reversebits_u2(instruction)), //check and fix
+        0x3C..=0x3F => return format!("LDX {}", reversebits_u2(instruction)),
+        0x40..=0x4F => return format!("TCY {}", reversebits_u4(instruction)),
+        0x50..=0x5F => return format!("YNEC {}", reversebits_u4(instruction)),
+        0x60..=0x6F => return format!("TCMIY {}", reversebits_u4(instruction)),
+        0x70..=0x7F => return format!("ALEC {}", reversebits_u4(instruction)),
+        0x80..=0xBF => return format!("BR {} ({})", instruction % 64, PC_SEQ.iter().position(|&i| i == (instruction % 64)).unwrap()),
+        0xC0..=0xFF => return format!("CALL {} ({})", instruction % 64, PC_SEQ.iter().position(|&i| i == (instruction % 64)).unwrap()),
       //  _ => return instruction.to_string(),
     }
 }
@@ -91,7 +94,7 @@ fn decodeinstruction_TMS1100(instruction : u8) -> String{
         0x0D => return String::from("SETR"),
         0x0E => return String::from("KNEZ"),
         0x0F => return String::from("RETN"),
-        0x10..=0x1F => return String::from("LDP ".to_owned() + &(reversebits_u4(instruction)).to_string()),
+        0x10..=0x1F => return format!("LDP {}", reversebits_u4(instruction)),
         0x20 => return String::from("TAY"),
         0x21 => return String::from("TMA"),
         0x22 => return String::from("TMY"),
@@ -100,48 +103,58 @@ fn decodeinstruction_TMS1100(instruction : u8) -> String{
         0x25 => return String::from("TAMIYC"),
         0x26 => return String::from("TAMZA"),
         0x27 => return String::from("TAM"),
-        0x28..=0x2F => return String::from("LDX ".to_owned() + &(reversebits_u3(instruction)).to_string()),
-        0x30..=0x33 => return String::from("SBIT ".to_owned() + &(reversebits_u2(instruction)).to_string()),
-        0x34..=0x37 => return String::from("RBIT ".to_owned() + &(reversebits_u2(instruction)).to_string()),
-        0x38..=0x3B => return String::from("TBIT1 ".to_owned() + &(reversebits_u2(instruction)).to_string()),
+        0x28..=0x2F => return format!("LDX {}", reversebits_u3(instruction)),
+        0x30..=0x33 => return format!("SBIT {}", reversebits_u2(instruction)),
+        0x34..=0x37 => return format!("RBIT {}", reversebits_u2(instruction)),
+        0x38..=0x3B => return format!("TBIT1 {}", reversebits_u2(instruction)),
         0x3C => return String::from("SAMAN"),
         0x3D => return String::from("CPAIZ"),
         0x3E => return String::from("IMAC"),
         0x3F => return String::from("MNEZ"),
-        0x40..=0x4F => return String::from("TCY ".to_owned() + &(reversebits_u4(instruction)).to_string()),
-        0x50..=0x5F => return String::from("YNEC ".to_owned() + &(reversebits_u4(instruction)).to_string()),
-        0x60..=0x6F => return String::from("TCMIY ".to_owned() + &(reversebits_u4(instruction)).to_string()),
+        0x40..=0x4F => return format!("TCY {}", reversebits_u4(instruction)),
+        0x50..=0x5F => return format!("YNEC {}", reversebits_u4(instruction)),
+        0x60..=0x6F => return format!("TCMIY {}", reversebits_u4(instruction)),
         0x70..=0x7E => return format!("A{}AAC", reversebits_u4(instruction) + 1), //double check
         0x7F => return String::from("CLA"),
-        0x80..=0xBF => return String::from("BR ".to_owned() + &(instruction % 64).to_string() + " (" + &(PC_SEQ.iter().position(|&i| i == (instruction % 64)).unwrap()).to_string() + ")"),
-        0xC0..=0xFF => return String::from("CALL ".to_owned() + &(instruction % 64).to_string() + " (" + &(PC_SEQ.iter().position(|&i| i == (instruction % 64)).unwrap()).to_string() + ")"),
+        0x80..=0xBF => return format!("BR {} ({})", instruction % 64, PC_SEQ.iter().position(|&i| i == (instruction % 64)).unwrap()),
+        0xC0..=0xFF => return format!("CALL {} ({})", instruction % 64, PC_SEQ.iter().position(|&i| i == (instruction % 64)).unwrap()),
     }
 }
 
-pub fn decodeinstruction(instruction : u8, version : u32) -> String {
+pub fn decodeinstruction(instruction : u8, version : ChipVariant) -> String {
     match version {
-        1100 | 1300 => return decodeinstruction_TMS1100(instruction.clone()),
-        _ => return decodeinstruction_TMS1000(instruction.clone()),
+        ChipVariant::TMS1100 | ChipVariant::TMS1300 => return decodeinstruction_TMS1100(instruction),
+        _ => return decodeinstruction_TMS1000(instruction),
     };
 }
 
-pub fn decompile(filename : String, version : u32) -> [String; 64 * 16 * 2]
+//One line of decompiler output, in the format compiler::compile reads back:
+//    C PP WW (EE) : MNEMONIC [operand [(EE)]]
+//chapter, page and raw PC, then the execution order index of the PC on its page
+pub fn format_line(chapter : usize, page : usize, pc : usize, mnemonic : &str) -> String {
+    let execorder = PC_SEQ.iter().position(|&i| i == (pc as u8)).unwrap();
+    return format!("{} {:0>2} {:0>2} ({:0>2}) : {}", chapter, page, pc, execorder, mnemonic);
+}
+
+pub fn decompile(filename : String, version : ChipVariant) -> [String; 64 * 16 * 2]
 {
     let file = File::open(&filename);
     let mut data: Vec<u8> = vec![];
     let _ = file.expect("REASON").read_to_end(&mut data);
+    return decompile_rom(&data, version);
+}
+
+//As decompile, from a ROM image already in memory
+pub fn decompile_rom(data : &[u8], version : ChipVariant) -> [String; 64 * 16 * 2]
+{
     let mut pcvalue: usize = 0;
     let mut pavalue: usize = 0;
     let mut chvalue: usize = 0;
-   // println!("{:?}", data);
     let mut results: [String; 64 * 16 * 2] = [const {String::new()}; 64 * 16 * 2];
-    for i in data.iter_mut() {
-        let decodedInstruction: String = match version {
-            1100 | 1300 => decodeinstruction_TMS1100(i.clone()),
-            _ => decodeinstruction_TMS1000(i.clone()),
-        };
+    for i in data.iter().take(64 * 16 * 2) {
+        let decodedInstruction: String = decodeinstruction(*i, version);
         let execorder = PC_SEQ.iter().position(|&i| i == (pcvalue  as u8)).unwrap();
-        results[(1024 * chvalue) + (64 * pavalue) + execorder] = format!("{} {:0>2} {:0>2} ({:0>2}) : {}", chvalue, pavalue, pcvalue, execorder, decodedInstruction);
+        results[(1024 * chvalue) + (64 * pavalue) + execorder] = format_line(chvalue, pavalue, pcvalue, &decodedInstruction);
         pcvalue += 1;
         if pcvalue == 64 {
             pcvalue = 0;
@@ -168,7 +181,7 @@ fn decompile_TMS1000(filename : String) -> [String; 64 * 16]
         //Reorders instructions in order of execution
         //(TMS1000 uses a pseudo-random program counter order, seen in PC_SEQ)
         let execorder = PC_SEQ.iter().position(|&i| i == (pcvalue  as u8)).unwrap();
-        results[(64 * pavalue) + execorder] = format!("{:0>2} {:0>2} : {}", pavalue, pcvalue, decodeinstruction_TMS1000(i.clone()));
+        results[(64 * pavalue) + execorder] = format_line(0, pavalue, pcvalue, &decodeinstruction_TMS1000(*i));
         pcvalue += 1;
         if pcvalue == 64 {
             pcvalue = 0;
@@ -192,7 +205,7 @@ fn decompile_TMS1100(filename : String) -> [String; 64 * 16 * 2]
         //Reorders instructions in order of execution
         //(TMS1000 uses a pseudo-random program counter order, seen in PC_SEQ)
         let execorder = PC_SEQ.iter().position(|&i| i == (pcvalue as u8)).unwrap();
-        results[(1024 * chvalue) + (64 * pavalue) + execorder] = format!("{} {:0>2} {:0>2} : {}", chvalue, pavalue, pcvalue, decodeinstruction_TMS1100(i.clone()));
+        results[(1024 * chvalue) + (64 * pavalue) + execorder] = format_line(chvalue, pavalue, pcvalue, &decodeinstruction_TMS1100(*i));
         pcvalue += 1;
         if pcvalue == 64 {
             pcvalue = 0;
@@ -206,6 +219,13 @@ fn decompile_TMS1100(filename : String) -> [String; 64 * 16 * 2]
     return results;
 }
 
+pub fn display(filename : String, version : ChipVariant) {
+    match version {
+        ChipVariant::TMS1100 | ChipVariant::TMS1300 => display_TMS1100(filename),
+        _ => display_TMS1000(filename),
+    }
+}
+
 pub fn display_TMS1000(filename : String) {
     let src = decompile_TMS1000(filename);
     for (idx, val) in src.iter().enumerate() {
@@ -0,0 +1,55 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Disassembler
+//Uses cfg::trace to find where each BR and CALL really lands. The output is compiler source:
+//reachable code in execution order with a label on every branch target, subroutine entry points
+//marked, and bytes no path reaches listed separately as .BYTE data.
+
+use crate::cfg::{self, rom_index};
+use crate::decompiler;
+use crate::variant::ChipVariant;
+use crate::TMS1000::{format_address, PC_SEQ};
+
+//Compiler source for the ROM, see the top of this file
+pub fn disassemble(rom : &[u8], version : ChipVariant) -> String {
+    let flow = cfg::trace(rom, version);
+    let mut results = format!("; Disassembled for the {}\n", version);
+    for chapter in 0..(flow.reachable.len() / 1024) {
+        for page in 0..16 {
+            let indexes: Vec<usize> = PC_SEQ.iter().map(|&pc| rom_index(chapter, page, pc)).filter(|&i| i < rom.len()).collect();
+            if indexes.is_empty() {
+                continue;
+            }
+            results.push_str(&format!("\n; Chapter {} page {}\n", chapter, page));
+            for &index in indexes.iter().filter(|&&i| flow.reachable[i]) {
+                if let Some(name) = flow.label(index) {
+                    match flow.subroutines.get(&index) {
+                        Some(callers) => {
+                            let from: Vec<String> = callers.iter().map(|&c| format_address(c)).collect();
+                            results.push_str(&format!("{}: ; subroutine, called from {}\n", name, from.join(", ")));
+                        },
+                        None => results.push_str(&format!("{}:\n", name)),
+                    }
+                }
+                let line = decompiler::format_line(chapter, page, index % 64, &decompiler::decodeinstruction(rom[index], version));
+                match flow.targets.get(&index) {
+                    Some(targets) => {
+                        let names: Vec<String> = targets.iter().map(|&t| flow.label(t).unwrap_or(format_address(t))).collect();
+                        results.push_str(&format!("{:<32} ; -> {}\n", line, names.join(", ")));
+                    },
+                    None => results.push_str(&format!("{}\n", line)),
+                }
+            }
+            let data: Vec<usize> = indexes.iter().copied().filter(|&i| !flow.reachable[i]).collect();
+            if !data.is_empty() {
+                results.push_str(&format!("; Unreachable, page {}\n", page));
+            }
+            for index in data {
+                let line = decompiler::format_line(chapter, page, index % 64, &format!(".BYTE ${:0>2X}", rom[index]));
+                results.push_str(&format!("{:<32} ; {}\n", line, decompiler::decodeinstruction(rom[index], version)));
+            }
+        }
+    }
+    return results;
+}
@@ -1,3 +1,13 @@
 pub mod TMS1000;
 pub mod decompiler;
+pub mod disassembler;
+pub mod cfg;
+pub mod reachability;
+pub mod coverage;
+pub mod profiler;
+pub mod trace;
+pub mod mame;
 pub mod compiler;
+pub mod assembler;
+pub mod variant;
+pub mod listing;
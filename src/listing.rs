@@ -0,0 +1,120 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Listing and symbol files written by the compile and assemble binaries
+//
+//Listing, one line per placed byte, in source order:
+//    file:line  C PP WW (EE) : BB  source
+//Symbol file, one label per line, in the decompiler's address format:
+//    NAME C PP WW (EE)
+//The tms debugger loads symbol files to name addresses.
+
+use std::collections::HashMap;
+use std::fs;
+use regex::Regex;
+
+use crate::TMS1000::exec_index;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListingLine {
+    pub file: String,
+    pub line: usize,
+    pub source: String,
+    pub chapter: usize,
+    pub page: usize,
+    pub pc: usize,
+    pub index: usize, //execution order within the page
+    pub byte: u8,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub chapter: usize,
+    pub page: usize,
+    pub pc: usize,
+}
+
+impl Symbol {
+    pub fn rom_index(&self) -> usize {
+        return (1024 * self.chapter) + (64 * self.page) + self.pc;
+    }
+}
+
+//ROM image along with where every byte came from
+#[derive(Clone, Debug)]
+pub struct Assembled {
+    pub rom: Vec<u8>,
+    pub listing: Vec<ListingLine>,
+    pub symbols: Vec<Symbol>,
+}
+
+impl Assembled {
+    //Writes the ROM, and the listing and symbol files where asked for
+    pub fn write_files(&self, rom_file : &str, listing_file : Option<String>, symbol_file : Option<String>) -> Result<(), String> {
+        if fs::write(rom_file, &self.rom).is_err() {
+            return Err(format!("Could not write {}", rom_file));
+        }
+        if let Some(v) = listing_file {
+            if fs::write(&v, format_listing(&self.listing)).is_err() {
+                return Err(format!("Could not write {}", v));
+            }
+        }
+        if let Some(v) = symbol_file {
+            if fs::write(&v, format_symbols(&self.symbols)).is_err() {
+                return Err(format!("Could not write {}", v));
+            }
+        }
+        return Ok(());
+    }
+}
+
+pub fn format_listing(listing : &[ListingLine]) -> String {
+    let mut results = String::new();
+    for entry in listing.iter() {
+        let origin = format!("{}:{}", entry.file, entry.line);
+        results.push_str(&format!("{:<24} {} {:0>2} {:0>2} ({:0>2}) : {:0>2X}  {}\n", origin, entry.chapter, entry.page, entry.pc, entry.index, entry.byte, entry.source));
+    }
+    return results;
+}
+
+pub fn format_symbols(symbols : &[Symbol]) -> String {
+    let mut results = String::new();
+    for symbol in symbols.iter() {
+        results.push_str(&format!("{} {} {:0>2} {:0>2} ({:0>2})\n", symbol.name, symbol.chapter, symbol.page, symbol.pc, exec_index(symbol.pc)));
+    }
+    return results;
+}
+
+pub fn parse_symbols(data : &str) -> Result<Vec<Symbol>, String> {
+    let symbol_re = Regex::new(r"^([A-Za-z_][A-Za-z0-9_]*)\s+([0-9])\s+([0-9]{1,2})\s+([0-9]{1,2})").unwrap();
+    let mut results: Vec<Symbol> = Vec::new();
+    for (idx, line) in data.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let v = match symbol_re.captures(line.trim()) {
+            Some(v) => v,
+            None => return Err(format!("Symbols: line {}: could not parse \"{}\"", idx + 1, line.trim())),
+        };
+        let (chapter, page, pc) = (v[2].parse().unwrap_or(0), v[3].parse().unwrap_or(0), v[4].parse().unwrap_or(0));
+        if chapter > 1 || page > 15 || pc > 63 {
+            return Err(format!("Symbols: line {}: address {} {} {} out of range", idx + 1, chapter, page, pc));
+        }
+        results.push(Symbol { name: v[1].to_string(), chapter, page, pc });
+    }
+    return Ok(results);
+}
+
+//Reads a symbol file into a map from ROM index to label
+pub fn read_symbols(filename : &str) -> Result<HashMap<usize, String>, String> {
+    let data = match fs::read_to_string(filename) {
+        Ok(v) => v,
+        Err(_) => return Err(format!("Symbols: Problem opening or reading {}", filename)),
+    };
+    let mut results: HashMap<usize, String> = HashMap::new();
+    for symbol in parse_symbols(&data)? {
+        results.entry(symbol.rom_index()).or_insert(symbol.name); //first label wins where several share an address
+    }
+    return Ok(results);
+}
@@ -0,0 +1,141 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Validation against MAME traces
+//MAME's tms1k core is the usual reference. Its debugger writes one line per instruction with
+//    trace merlin.log,maincpu,noloop,{tracelog "A=%X X=%X Y=%X S=%X PA=%X PB=%X CA=%X CB=%X ",a,x,y,status,pa,pb,ca,cb}
+//giving the registers before the instruction runs, then the ROM address (chapter, page and raw PC
+//as ((chapter * 16) + page) * 64 + pc, in hexadecimal) and the disassembly:
+//    A=0 X=0 Y=3 S=1 PA=F PB=F CA=0 CB=0 3C1: TCY 3
+//Any subset of A, X, Y, S (or STATUS), SL, PA, PB, PC, SR, CA, CB, CS (or CSL), CL, R and O may be
+//logged, in any order. Lines starting with # are comments. "noloop" matters: without it MAME folds
+//loops into a single "(loops for N instructions)" line, and the states inside are lost.
+//The only trace in the tests, tests/fixtures/self_generated_merlin.log, was written by this emulator,
+//so it exercises this harness but says nothing about agreement with MAME.
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::TMS1000::SYSTEM;
+
+const REGISTERS : [&str; 17] = ["A", "X", "Y", "S", "STATUS", "SL", "PA", "PB", "PC", "SR", "CA", "CB", "CS", "CSL", "CL", "R", "O"];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MameRecord {
+    pub line: usize,
+    pub rom_address: usize,
+    pub registers: BTreeMap<String, u32>, //as logged, before the instruction runs
+    pub disassembly: String,
+}
+
+impl MameRecord {
+    pub fn parse(line : &str, line_number : usize) -> Result<MameRecord, String> {
+        let mut registers: BTreeMap<String, u32> = BTreeMap::new();
+        let mut fields = line.split_whitespace();
+        loop {
+            let field = match fields.next() {
+                Some(v) => v,
+                None => return Err(format!("MAME trace: line {}: no address", line_number)),
+            };
+            if let Some(address) = field.strip_suffix(':') {
+                let rom_address = match usize::from_str_radix(address, 16) {
+                    Ok(v) => v,
+                    Err(_) => return Err(format!("MAME trace: line {}: \"{}\" is not an address", line_number, address)),
+                };
+                return Ok(MameRecord { line: line_number, rom_address, registers, disassembly: fields.collect::<Vec<&str>>().join(" ") });
+            }
+            let (name, value) = match field.split_once('=') {
+                Some(v) => v,
+                None => return Err(format!("MAME trace: line {}: expected NAME=value, got \"{}\"", line_number, field)),
+            };
+            let name = name.to_uppercase();
+            if !REGISTERS.contains(&name.as_str()) {
+                return Err(format!("MAME trace: line {}: unknown register {}", line_number, name));
+            }
+            match u32::from_str_radix(value, 16) {
+                Ok(v) => registers.insert(name, v),
+                Err(_) => return Err(format!("MAME trace: line {}: \"{}\" is not a hexadecimal number", line_number, value)),
+            };
+        }
+    }
+}
+
+pub fn parse_mame_trace(data : &str) -> Result<Vec<MameRecord>, String> {
+    let mut results: Vec<MameRecord> = Vec::new();
+    for (idx, line) in data.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if trimmed.starts_with("(loops") {
+            return Err(format!("MAME trace: line {}: loop folded by MAME; record the trace with noloop", idx + 1));
+        }
+        results.push(MameRecord::parse(trimmed, idx + 1)?);
+    }
+    return Ok(results);
+}
+
+pub fn read_mame_trace(filename : &str) -> Result<Vec<MameRecord>, String> {
+    return match fs::read_to_string(filename) {
+        Ok(v) => parse_mame_trace(&v),
+        Err(_) => Err(format!("MAME trace: Problem opening or reading {}", filename)),
+    };
+}
+
+//Our value for a register named in a MAME trace, at the point the next instruction is about to run
+fn register(system : &mut SYSTEM, name : &str) -> u32 {
+    return match name {
+        "A" => system.get_acc_reg() as u32,
+        "X" => system.get_x_reg() as u32,
+        "Y" => system.get_y_reg() as u32,
+        "S" | "STATUS" => system.get_status() as u32,
+        "SL" => system.get_sl_reg() as u32,
+        "PA" => system.get_pa_reg() as u32,
+        "PB" => system.get_pb_reg() as u32,
+        "PC" => system.get_pc_reg() as u32,
+        "SR" => system.get_sr_reg() as u32,
+        "CA" => system.get_ca_reg() as u32,
+        "CB" => system.get_cb_reg() as u32,
+        "CS" | "CSL" => system.get_csl_reg() as u32,
+        "CL" => system.get_cl_reg() as u32,
+        "R" => system.get_r_outputs_u32(),
+        "O" => system.get_o_outputs(),
+        _ => 0,
+    };
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub index: usize, //record number, from 0
+    pub line: usize,
+    pub field: String, //"address" or a register name
+    pub expected: u32, //from MAME
+    pub actual: u32,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        return write!(f, "instruction {} (line {}): {} is {:X}, MAME has {:X}", self.index, self.line, self.field, self.actual, self.expected);
+    }
+}
+
+//Steps a freshly loaded system through the trace, holding `k_input` on the K lines, and checks the
+//address and every logged register before each instruction. Returns the number of instructions
+//checked, or the first mismatch.
+pub fn check(system : &mut SYSTEM, records : &[MameRecord], k_input : u8) -> Result<usize, Mismatch> {
+    system.instruction_cycle_mut(k_input); //fetches the instruction at the reset vector
+    for (index, record) in records.iter().enumerate() {
+        let address = system.get_instruction_address();
+        if address != record.rom_address {
+            return Err(Mismatch { index, line: record.line, field: "address".to_string(), expected: record.rom_address as u32, actual: address as u32 });
+        }
+        for (name, &expected) in record.registers.iter() {
+            let actual = register(system, name);
+            if actual != expected {
+                return Err(Mismatch { index, line: record.line, field: name.clone(), expected, actual });
+            }
+        }
+        system.instruction_cycle_mut(k_input);
+    }
+    return Ok(records.len());
+}
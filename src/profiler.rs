@@ -0,0 +1,99 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Execution profiler
+//Recorded by SYSTEM once enabled with set_profiling. Every instruction cycle is charged to the ROM
+//byte it executes and to the subroutine it runs in. The TMS1000 family has a single subroutine
+//level: a routine is entered when a CALL sets the call latch and left when RETN clears it, and a
+//CALL made inside a subroutine only jumps, so stays charged to the routine that made it.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::TMS1000::format_address;
+
+pub const TOP_LEVEL : &str = "main";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Profile {
+    pub cycles: Vec<u64>, //by ROM index
+    pub subroutine_cycles: BTreeMap<usize, u64>, //by ROM index of the entry point
+    pub top_level_cycles: u64, //outside any subroutine
+    current: Option<usize>, //entry point of the running subroutine
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        return Profile::new();
+    }
+}
+
+impl Profile {
+    pub fn new() -> Self {
+        return Profile { cycles: vec![0; 2048], subroutine_cycles: BTreeMap::new(), top_level_cycles: 0, current: None };
+    }
+
+    //Charges the cycle that will execute the instruction just fetched
+    pub(crate) fn record(&mut self, rom_index : usize, call_latch : bool) {
+        self.current = match (call_latch, self.current) {
+            (false, _) => None,
+            (true, Some(entry)) => Some(entry),
+            (true, None) => Some(rom_index), //fetched straight after the CALL, so the entry point
+        };
+        if let Some(v) = self.cycles.get_mut(rom_index) {
+            *v += 1;
+        }
+        match self.current {
+            Some(entry) => *self.subroutine_cycles.entry(entry).or_insert(0) += 1,
+            None => self.top_level_cycles += 1,
+        }
+    }
+
+    pub fn total_cycles(&self) -> u64 {
+        return self.cycles.iter().sum();
+    }
+
+    fn name(entry : usize, symbols : &HashMap<usize, String>) -> String {
+        return match symbols.get(&entry) {
+            Some(v) => v.clone(),
+            None => format!("S_{}_{:0>2}_{:0>2}", entry / 1024, (entry / 64) % 16, entry % 64),
+        };
+    }
+
+    //Subroutines and the busiest addresses, most cycles first; symbols (see listing::read_symbols) name entry points
+    pub fn report(&self, symbols : &HashMap<usize, String>, top : usize) -> String {
+        let total = self.total_cycles().max(1);
+        let percent = |v : u64| -> f64 { return 100.0 * v as f64 / total as f64; };
+        let mut results = format!("{} cycles\n\nBy subroutine\n", self.total_cycles());
+
+        let mut routines: Vec<(String, u64)> = self.subroutine_cycles.iter().map(|(&entry, &v)| (Profile::name(entry, symbols), v)).collect();
+        routines.push((TOP_LEVEL.to_string(), self.top_level_cycles));
+        routines.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        for (name, v) in routines {
+            results.push_str(&format!("{:>12} {:>6.2}%  {}\n", v, percent(v), name));
+        }
+
+        results.push_str("\nBy address\n");
+        let mut addresses: Vec<(usize, u64)> = self.cycles.iter().copied().enumerate().filter(|(_, v)| *v > 0).collect();
+        addresses.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        for (index, v) in addresses.into_iter().take(top) {
+            let label = match symbols.get(&index) {
+                Some(name) => format!("  {}", name),
+                None => String::new(),
+            };
+            results.push_str(&format!("{:>12} {:>6.2}%  {}{}\n", v, percent(v), format_address(index), label));
+        }
+        return results;
+    }
+
+    //One line per stack, "main;routine cycles", as read by flamegraph.pl and inferno
+    pub fn folded(&self, symbols : &HashMap<usize, String>) -> String {
+        let mut results = String::new();
+        if self.top_level_cycles > 0 {
+            results.push_str(&format!("{} {}\n", TOP_LEVEL, self.top_level_cycles));
+        }
+        for (&entry, &v) in self.subroutine_cycles.iter() {
+            results.push_str(&format!("{};{} {}\n", TOP_LEVEL, Profile::name(entry, symbols), v));
+        }
+        return results;
+    }
+}
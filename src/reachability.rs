@@ -0,0 +1,128 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Reachability report
+//What cfg::build finds, summarised for patching a ROM: which bytes can run, which runs of bytes
+//never do (free space, in execution order), and branches worth a second look.
+
+use crate::cfg::{self, rom_index, EdgeKind};
+use crate::decompiler;
+use crate::variant::ChipVariant;
+use crate::TMS1000::{exec_index, format_address, PC_SEQ};
+
+//Unreachable bytes following one another in execution order on a page
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FreeRun {
+    pub chapter: usize,
+    pub page: usize,
+    pub first: usize, //execution index
+    pub last: usize,
+}
+
+impl FreeRun {
+    pub fn bytes(&self) -> usize {
+        return self.last - self.first + 1;
+    }
+}
+
+//A BR or CALL that deserves a look
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub from: usize, //ROM index of the branch
+    pub to: usize,
+    pub message: String,
+}
+
+pub struct ReachabilityReport {
+    pub size: usize,
+    pub reachable: Vec<bool>,
+    pub free: Vec<FreeRun>,
+    pub mid_routine: Vec<Finding>,
+    pub cross_page: Vec<Finding>,
+}
+
+fn previous_in_page(index : usize) -> usize {
+    let exec = exec_index(index % 64);
+    return (index & !63) + PC_SEQ[(exec + 63) % 64];
+}
+
+pub fn report(rom : &[u8], version : ChipVariant) -> ReachabilityReport {
+    let graph = cfg::build(rom, version);
+    let flow = &graph.flow;
+    let size = flow.reachable.len().min(rom.len());
+
+    let mut free: Vec<FreeRun> = Vec::new();
+    for chapter in 0..(size / 1024).max(1) {
+        for page in 0..16 {
+            if rom_index(chapter, page, 0) >= size {
+                continue;
+            }
+            let mut run: Option<FreeRun> = None;
+            for (exec, &pc) in PC_SEQ.iter().enumerate() {
+                if flow.reachable[rom_index(chapter, page, pc)] {
+                    free.extend(run.take());
+                }
+                else {
+                    match run.as_mut() {
+                        Some(v) => v.last = exec,
+                        None => run = Some(FreeRun { chapter, page, first: exec, last: exec }),
+                    }
+                }
+            }
+            free.extend(run.take());
+        }
+    }
+
+    //Subroutine entries that code also falls into from the instruction before
+    let mut mid_routine: Vec<Finding> = Vec::new();
+    for (entry, callers) in flow.subroutines.iter() {
+        let from = previous_in_page(*entry);
+        let falls_in = flow.edges.iter().any(|e| e.from == from && e.to == *entry && (e.kind == EdgeKind::FallThrough || e.kind == EdgeKind::NotTaken));
+        if falls_in {
+            for &caller in callers.iter() {
+                mid_routine.push(Finding { from: caller, to: *entry, message: format!("also reached by falling through from {}", format_address(from)) });
+            }
+        }
+    }
+
+    //Branches changing page on a PB value loaded before the start of their basic block
+    let mut cross_page: Vec<Finding> = Vec::new();
+    for block in graph.blocks.iter() {
+        let end = block.end();
+        let loads_page = block.instructions.iter().any(|&i| matches!(rom.get(i), Some(0x10..=0x1F)));
+        for edge in flow.edges.iter().filter(|e| e.from == end && (e.kind == EdgeKind::Branch || e.kind == EdgeKind::Call)) {
+            if (edge.to / 64) % 16 != (end / 64) % 16 && !loads_page { //a change of chapter alone needs COMC, not LDP
+                cross_page.push(Finding { from: end, to: edge.to, message: "page buffer loaded before the start of the block".to_string() });
+            }
+        }
+    }
+
+    return ReachabilityReport { size, reachable: flow.reachable.clone(), free, mid_routine, cross_page };
+}
+
+impl ReachabilityReport {
+    pub fn reachable_count(&self) -> usize {
+        return self.reachable.iter().take(self.size).filter(|&&r| r).count();
+    }
+
+    pub fn format(&self, rom : &[u8], version : ChipVariant) -> String {
+        let mut results = String::new();
+        results.push_str(&format!("Reachable from reset: {} of {} bytes\n", self.reachable_count(), self.size));
+        results.push_str(&format!("Never executed: {} bytes\n", self.size - self.reachable_count()));
+        results.push_str("\nFree runs (execution order)\n");
+        let mut runs: Vec<&FreeRun> = self.free.iter().collect();
+        runs.sort_by_key(|r| std::cmp::Reverse(r.bytes()));
+        for run in runs {
+            results.push_str(&format!("  chapter {} page {:0>2}: index {:0>2}-{:0>2}, {} bytes (PC {:0>2} to {:0>2})\n", run.chapter, run.page, run.first, run.last, run.bytes(), PC_SEQ[run.first], PC_SEQ[run.last]));
+        }
+        let sections = [("Subroutine entries in the middle of a routine", &self.mid_routine), ("Cross-page branches without an LDP in their block", &self.cross_page)];
+        for (title, findings) in sections {
+            results.push_str(&format!("\n{} ({})\n", title, findings.len()));
+            for finding in findings.iter() {
+                let instruction = decompiler::decodeinstruction(*rom.get(finding.from).unwrap_or(&0), version);
+                results.push_str(&format!("  {} : {} -> {}: {}\n", format_address(finding.from), instruction, format_address(finding.to), finding.message));
+            }
+        }
+        return results;
+    }
+}
@@ -0,0 +1,314 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Instruction-level traces
+//One line per instruction cycle: the instruction executed, then the registers and outputs once it
+//has run. Numbers are hexadecimal, the mnemonic takes the rest of the line:
+//    cycle C:PP:WW opcode A X Y S SL PA PB CL K R O mnemonic
+//e.g.
+//    1a 0:0f:00 4c 0 0 3 1 0 f f 0 0 0 0 TCY 3
+//Lines starting with # are comments.
+
+use std::fmt;
+use std::fs;
+use std::io::Write;
+
+use crate::decompiler;
+use crate::variant::ChipVariant;
+use crate::TMS1000::SYSTEM;
+
+pub const HEADER : &str = "# cycle C:PP:WW opcode A X Y S SL PA PB CL K R O mnemonic";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceRecord {
+    pub cycle: u64,
+    pub rom_address: usize, //of the instruction executed
+    pub opcode: u8,
+    pub mnemonic: String,
+    pub accumulator: u8,
+    pub x: usize,
+    pub y: usize,
+    pub status: u8,
+    pub status_latch: u8,
+    pub page_address: u8,
+    pub page_buffer: u8,
+    pub call_latch: u8,
+    pub k_input: u8,
+    pub r_outputs: u32,
+    pub o_outputs: u32,
+}
+
+impl TraceRecord {
+    //Runs one instruction cycle and records it
+    pub fn step(system : &mut SYSTEM, version : ChipVariant, cycle : u64, k_input : u8) -> TraceRecord {
+        let rom_address = system.get_instruction_address();
+        let opcode = system.get_instruction();
+        system.instruction_cycle_mut(k_input);
+        return TraceRecord {
+            cycle,
+            rom_address,
+            opcode,
+            mnemonic: decompiler::decodeinstruction(opcode, version),
+            accumulator: system.get_acc_reg(),
+            x: system.get_x_reg(),
+            y: system.get_y_reg(),
+            status: system.get_status(),
+            status_latch: system.get_sl_reg(),
+            page_address: system.get_pa_reg(),
+            page_buffer: system.get_pb_reg(),
+            call_latch: system.get_cl_reg(),
+            k_input,
+            r_outputs: system.get_r_outputs_u32(),
+            o_outputs: system.get_o_outputs(),
+        };
+    }
+
+    //Names of the fields that differ, the cycle number aside
+    pub fn differences(&self, other : &TraceRecord) -> Vec<&'static str> {
+        let mut results: Vec<&'static str> = Vec::new();
+        let fields: [(&'static str, bool); 14] = [
+            ("address", self.rom_address != other.rom_address),
+            ("opcode", self.opcode != other.opcode),
+            ("A", self.accumulator != other.accumulator),
+            ("X", self.x != other.x),
+            ("Y", self.y != other.y),
+            ("S", self.status != other.status),
+            ("SL", self.status_latch != other.status_latch),
+            ("PA", self.page_address != other.page_address),
+            ("PB", self.page_buffer != other.page_buffer),
+            ("CL", self.call_latch != other.call_latch),
+            ("K", self.k_input != other.k_input),
+            ("R", self.r_outputs != other.r_outputs),
+            ("O", self.o_outputs != other.o_outputs),
+            ("mnemonic", self.mnemonic != other.mnemonic),
+        ];
+        for (name, differs) in fields {
+            if differs {
+                results.push(name);
+            }
+        }
+        return results;
+    }
+
+    pub fn parse(line : &str) -> Result<TraceRecord, String> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 15 {
+            return Err(format!("Trace: expected 15 fields or more, got \"{}\"", line));
+        }
+        let hex = |idx : usize| -> Result<u64, String> {
+            return u64::from_str_radix(fields[idx], 16).map_err(|_| format!("Trace: \"{}\" is not a hexadecimal number", fields[idx]));
+        };
+        let address: Vec<&str> = fields[1].split(':').collect();
+        if address.len() != 3 {
+            return Err(format!("Trace: \"{}\" is not an address", fields[1]));
+        }
+        let mut rom_address: usize = 0;
+        for (part, scale) in address.iter().zip([1024, 64, 1]) {
+            rom_address += scale * usize::from_str_radix(part, 16).map_err(|_| format!("Trace: \"{}\" is not an address", fields[1]))?;
+        }
+        return Ok(TraceRecord {
+            cycle: hex(0)?,
+            rom_address,
+            opcode: hex(2)? as u8,
+            accumulator: hex(3)? as u8,
+            x: hex(4)? as usize,
+            y: hex(5)? as usize,
+            status: hex(6)? as u8,
+            status_latch: hex(7)? as u8,
+            page_address: hex(8)? as u8,
+            page_buffer: hex(9)? as u8,
+            call_latch: hex(10)? as u8,
+            k_input: hex(11)? as u8,
+            r_outputs: hex(12)? as u32,
+            o_outputs: hex(13)? as u32,
+            mnemonic: fields[14..].join(" "),
+        });
+    }
+}
+
+impl fmt::Display for TraceRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{:x} {:x}:{:0>2x}:{:0>2x} {:0>2x} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {}",
+            self.cycle, self.rom_address / 1024, (self.rom_address / 64) % 16, self.rom_address % 64, self.opcode,
+            self.accumulator, self.x, self.y, self.status, self.status_latch, self.page_address, self.page_buffer, self.call_latch,
+            self.k_input, self.r_outputs, self.o_outputs, self.mnemonic);
+    }
+}
+
+//Writes records as they are made, to a file or anything else
+pub struct TraceWriter<W: Write> {
+    output: W,
+}
+
+impl<W: Write> TraceWriter<W> {
+    pub fn new(mut output : W) -> Result<Self, String> {
+        writeln!(output, "{}", HEADER).map_err(|e| format!("Trace: {}", e))?;
+        return Ok(TraceWriter { output });
+    }
+
+    pub fn write(&mut self, record : &TraceRecord) -> Result<(), String> {
+        return writeln!(self.output, "{}", record).map_err(|e| format!("Trace: {}", e));
+    }
+
+    pub fn into_inner(self) -> W {
+        return self.output;
+    }
+}
+
+pub fn parse_trace(data : &str) -> Result<Vec<TraceRecord>, String> {
+    let mut results: Vec<TraceRecord> = Vec::new();
+    for (idx, line) in data.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match TraceRecord::parse(line) {
+            Ok(v) => results.push(v),
+            Err(e) => return Err(format!("line {}: {}", idx + 1, e)),
+        }
+    }
+    return Ok(results);
+}
+
+pub fn read_trace(filename : &str) -> Result<Vec<TraceRecord>, String> {
+    return match fs::read_to_string(filename) {
+        Ok(v) => parse_trace(&v).map_err(|e| format!("{}: {}", filename, e)),
+        Err(_) => Err(format!("Trace: Problem opening or reading {}", filename)),
+    };
+}
+
+//Where two traces first part ways; a record is None where that trace has already ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    pub index: usize,
+    pub left: Option<TraceRecord>,
+    pub right: Option<TraceRecord>,
+    pub fields: Vec<&'static str>,
+}
+
+pub fn first_divergence(left : &[TraceRecord], right : &[TraceRecord]) -> Option<Divergence> {
+    for index in 0..left.len().max(right.len()) {
+        let (a, b) = (left.get(index), right.get(index));
+        let fields = match (a, b) {
+            (Some(a), Some(b)) => a.differences(b),
+            _ => vec!["length"],
+        };
+        if !fields.is_empty() {
+            return Some(Divergence { index, left: a.cloned(), right: b.cloned(), fields });
+        }
+    }
+    return None;
+}
+
+//Everything a program can observe about a SYSTEM after an instruction cycle: the trace record, the
+//chapter and subroutine registers it leaves out, and RAM
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub record: TraceRecord,
+    pub chapter_address: usize,
+    pub chapter_buffer: usize,
+    pub chapter_subroutine_latch: usize,
+    pub program_counter: usize,
+    pub subroutine_return: usize,
+    pub ram: [[u8; 16]; 8],
+}
+
+impl Snapshot {
+    pub fn step(system : &mut SYSTEM, version : ChipVariant, cycle : u64, k_input : u8) -> Snapshot {
+        let record = TraceRecord::step(system, version, cycle, k_input);
+        return Snapshot {
+            record,
+            chapter_address: system.get_ca_reg(),
+            chapter_buffer: system.get_cb_reg(),
+            chapter_subroutine_latch: system.get_csl_reg(),
+            program_counter: system.get_pc_reg(),
+            subroutine_return: system.get_sr_reg(),
+            ram: system.get_ram_array(),
+        };
+    }
+
+    //As TraceRecord::differences, with RAM words named "RAM x,y"
+    pub fn differences(&self, other : &Snapshot) -> Vec<String> {
+        let mut results: Vec<String> = self.record.differences(&other.record).iter().map(|v| v.to_string()).collect();
+        let fields = [
+            ("CA", self.chapter_address != other.chapter_address),
+            ("CB", self.chapter_buffer != other.chapter_buffer),
+            ("CSL", self.chapter_subroutine_latch != other.chapter_subroutine_latch),
+            ("PC", self.program_counter != other.program_counter),
+            ("SR", self.subroutine_return != other.subroutine_return),
+        ];
+        for (name, differs) in fields {
+            if differs {
+                results.push(name.to_string());
+            }
+        }
+        for x in 0..8 {
+            for y in 0..16 {
+                if self.ram[x][y] != other.ram[x][y] {
+                    results.push(format!("RAM {},{}", x, y));
+                }
+            }
+        }
+        return results;
+    }
+
+    //Both states in two columns, differing rows marked with *
+    pub fn side_by_side(&self, other : &Snapshot) -> String {
+        let hex_row = |v : &[u8]| -> String { return v.iter().map(|n| format!("{:x}", n)).collect(); };
+        let rows = |s : &Snapshot| -> Vec<(String, String)> {
+            let r = &s.record;
+            let mut rows = vec![
+                ("address".to_string(), format!("{}:{:0>2x}:{:0>2x}", r.rom_address / 1024, (r.rom_address / 64) % 16, r.rom_address % 64)),
+                ("opcode".to_string(), format!("{:0>2x} {}", r.opcode, r.mnemonic)),
+                ("A".to_string(), format!("{:x}", r.accumulator)),
+                ("X".to_string(), format!("{:x}", r.x)),
+                ("Y".to_string(), format!("{:x}", r.y)),
+                ("S".to_string(), format!("{:x}", r.status)),
+                ("SL".to_string(), format!("{:x}", r.status_latch)),
+                ("CA".to_string(), format!("{:x}", s.chapter_address)),
+                ("CB".to_string(), format!("{:x}", s.chapter_buffer)),
+                ("CSL".to_string(), format!("{:x}", s.chapter_subroutine_latch)),
+                ("PA".to_string(), format!("{:x}", r.page_address)),
+                ("PB".to_string(), format!("{:x}", r.page_buffer)),
+                ("PC".to_string(), format!("{:0>2}", s.program_counter)),
+                ("SR".to_string(), format!("{:0>2}", s.subroutine_return)),
+                ("CL".to_string(), format!("{:x}", r.call_latch)),
+                ("K".to_string(), format!("{:x}", r.k_input)),
+                ("R".to_string(), format!("{:x}", r.r_outputs)),
+                ("O".to_string(), format!("{:x}", r.o_outputs)),
+            ];
+            for (x, file) in s.ram.iter().enumerate() {
+                rows.push((format!("RAM {}", x), hex_row(file)));
+            }
+            return rows;
+        };
+        let mut results = String::new();
+        for ((name, left), (_, right)) in rows(self).into_iter().zip(rows(other)) {
+            let mark = if left != right { "*" } else { " " };
+            results.push_str(&format!("{} {:<8} {:<20} {}\n", mark, name, left, right));
+        }
+        return results;
+    }
+}
+
+//Where two systems run in lockstep first part ways
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateDivergence {
+    pub cycle: u64,
+    pub left: Snapshot,
+    pub right: Snapshot,
+    pub fields: Vec<String>,
+}
+
+//Runs both systems one instruction cycle at a time with the same K inputs, stopping at the first
+//cycle after which their states differ
+pub fn lockstep(left : &mut SYSTEM, right : &mut SYSTEM, version : ChipVariant, cycles : u64, k_input : u8) -> Option<StateDivergence> {
+    for cycle in 0..cycles {
+        let a = Snapshot::step(left, version, cycle, k_input);
+        let b = Snapshot::step(right, version, cycle, k_input);
+        let fields = a.differences(&b);
+        if !fields.is_empty() {
+            return Some(StateDivergence { cycle, left: a, right: b, fields });
+        }
+    }
+    return None;
+}
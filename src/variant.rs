@@ -0,0 +1,110 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Supported chip variants
+//Parsed from the version argument of every binary, e.g. "1100" or "TMS1100"
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChipVariant {
+    TMS1000,
+    TMS1070,
+    TMS1100,
+    TMS1200,
+    TMS1270,
+    TMS1300,
+}
+
+impl ChipVariant {
+    pub const ALL : [ChipVariant; 6] = [ChipVariant::TMS1000, ChipVariant::TMS1070, ChipVariant::TMS1100, ChipVariant::TMS1200, ChipVariant::TMS1270, ChipVariant::TMS1300];
+
+    //TMS1100/1300 have the alternate instruction set, eight RAM files and chapter addressing
+    pub fn is_TMS1100(&self) -> bool {
+        return matches!(self, ChipVariant::TMS1100 | ChipVariant::TMS1300);
+    }
+
+    //RAM files of 16 words each in the TMS1000 family, addressed by X
+    pub fn ram_files(&self) -> usize {
+        if self.is_TMS1100() {
+            return 8;
+        }
+        return 4;
+    }
+
+    //Bytes of program ROM: 16 pages of 64 per chapter, and two chapters on TMS1100/1300
+    pub fn rom_size(&self) -> usize {
+        return match self {
+            ChipVariant::TMS1100 | ChipVariant::TMS1300 => 2048,
+            _ => 1024,
+        };
+    }
+
+    //Fits a ROM image to the chip's ROM size, with a warning when the image was the wrong size
+    //A short image whose length divides the ROM size is mirrored, the way a smaller ROM wired to the
+    //same address lines would appear (a 1K image repeats in chapter 1 of a TMS1100); any other short
+    //image is padded with 0. Long images are cut to the ROM size.
+    pub fn fit_rom(&self, rom : &[u8]) -> (Vec<u8>, Option<String>) {
+        let size = self.rom_size();
+        if rom.len() == size {
+            return (rom.to_vec(), None);
+        }
+        let problem = format!("ROM warning: the image is {} bytes, but the {} has {} bytes of ROM", rom.len(), self, size);
+        if rom.len() > size {
+            return (rom[..size].to_vec(), Some(format!("{}; the last {} bytes are ignored", problem, rom.len() - size)));
+        }
+        #[allow(clippy::manual_is_multiple_of)] //usize::is_multiple_of needs Rust 1.87
+        if !rom.is_empty() && size % rom.len() == 0 {
+            return (rom.iter().cycle().take(size).copied().collect(), Some(format!("{}; mirrored {} times", problem, size / rom.len())));
+        }
+        let mut fitted = rom.to_vec();
+        fitted.resize(size, 0);
+        return (fitted, Some(format!("{}; padded with 0", problem)));
+    }
+
+    pub fn r_output_count(&self) -> usize {
+        return match self {
+            ChipVariant::TMS1200 | ChipVariant::TMS1270 => 13,
+            ChipVariant::TMS1300 => 16,
+            _ => 11,
+        };
+    }
+
+    pub fn number(&self) -> u32 {
+        return match self {
+            ChipVariant::TMS1000 => 1000,
+            ChipVariant::TMS1070 => 1070,
+            ChipVariant::TMS1100 => 1100,
+            ChipVariant::TMS1200 => 1200,
+            ChipVariant::TMS1270 => 1270,
+            ChipVariant::TMS1300 => 1300,
+        };
+    }
+}
+
+impl FromStr for ChipVariant {
+    type Err = String;
+
+    //Accepts the bare part number ("1100") with or without a "TMS" prefix
+    //The digits must be the part number exactly, so "01100" or "+1100" are rejected
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let digits = match trimmed.get(..3) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("tms") => &trimmed[3..],
+            _ => trimmed,
+        };
+        for variant in ChipVariant::ALL {
+            if digits == variant.number().to_string() {
+                return Ok(variant);
+            }
+        }
+        return Err(format!("Unknown chip variant \"{}\" (expected one of 1000, 1070, 1100, 1200, 1270, 1300)", s));
+    }
+}
+
+impl fmt::Display for ChipVariant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "TMS{:0>4}", self.number());
+    }
+}
@@ -0,0 +1,96 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Out of range memory access policies
+//The sentinel power-on policy leaves 255 in X and Y, so the first RAM access of a program that never
+//loads X is out of range on every chip.
+
+use std::collections::HashMap;
+
+use tms::TMS1000::{AccessPolicy, PowerOnPolicy, SYSTEM};
+use tms::variant::ChipVariant;
+
+mod common;
+
+//Stores 5 at the word addressed by TCY 5 and whatever X powered up as
+const STORE : &str = "TCY 5|TYA|TAM|CLA";
+
+fn load(version : ChipVariant, source : &str, policy : AccessPolicy) -> SYSTEM {
+    let mut system = common::load(version, source, PowerOnPolicy::Sentinel);
+    system.set_access_policy(policy);
+    return system;
+}
+
+#[test]
+fn policy_names() {
+    assert_eq!("panic".parse::<AccessPolicy>(), Ok(AccessPolicy::Panic));
+    assert_eq!(" Wrap\n".parse::<AccessPolicy>(), Ok(AccessPolicy::Wrap));
+    assert_eq!("HALT".parse::<AccessPolicy>(), Ok(AccessPolicy::Halt));
+    assert!("ignore".parse::<AccessPolicy>().is_err());
+}
+
+#[test]
+fn wrap_is_the_default() {
+    let system = SYSTEM::from_tables(ChipVariant::TMS1000, vec![0; 1024], HashMap::new(), HashMap::new(), PowerOnPolicy::Zero).unwrap();
+    assert_eq!(system.get_access_policy(), AccessPolicy::Wrap);
+}
+
+#[test]
+fn wrap_uses_the_files_a_TMS1000_has() {
+    let mut system = load(ChipVariant::TMS1000, STORE, AccessPolicy::Wrap);
+    common::run(&mut system, 5, 0);
+    assert_eq!(system.get_ram_array()[3][5], 5); //255 % 4
+    assert!(!system.is_halted());
+    assert!(system.get_access_faults().is_empty());
+}
+
+#[test]
+fn wrap_uses_the_files_a_TMS1100_has() {
+    let mut system = load(ChipVariant::TMS1100, STORE, AccessPolicy::Wrap);
+    common::run(&mut system, 5, 0);
+    assert_eq!(system.get_ram_array()[7][5], 5); //255 % 8
+}
+
+#[test]
+fn halt_records_a_fault_and_stops() {
+    let mut system = load(ChipVariant::TMS1000, STORE, AccessPolicy::Halt);
+    common::run(&mut system, 4, 0);
+    assert!(system.is_halted());
+    let faults = system.get_access_faults();
+    assert_eq!(faults.len(), 1);
+    assert_eq!(faults[0].rom_address, system.get_instruction_address());
+    assert!(faults[0].access.contains("RAM file 255"), "{}", faults[0].access);
+    assert!(system.get_ram_array().iter().all(|file| file.iter().all(|&word| word == 255)), "the faulting write went through");
+
+    let address = system.get_instruction_address();
+    let acc = system.get_acc_reg();
+    common::run(&mut system, 10, 0);
+    assert_eq!(system.get_instruction_address(), address);
+    assert_eq!(system.get_acc_reg(), acc); //CLA never ran
+    assert!(system.get_access_faults().is_empty(), "faults are only recorded once");
+
+    system.INITIALIZE();
+    assert!(!system.is_halted());
+}
+
+#[test]
+fn halting_stops_the_rest_of_the_instruction_cycle() {
+    //AMAAC reads RAM in its first step; the C8 and AUTA after the read must not run
+    let mut system = load(ChipVariant::TMS1000, "TCY 5|TYA|AMAAC|TCY 9", AccessPolicy::Halt);
+    common::run(&mut system, 10, 0);
+    assert!(system.is_halted());
+    assert_eq!((system.get_acc_reg(), system.get_y_reg(), system.get_status()), (5, 5, 1));
+}
+
+#[test]
+fn in_range_accesses_never_fault() {
+    let mut system = load(ChipVariant::TMS1000, "LDX 2|TCY 15|TCMIY 6|TAM", AccessPolicy::Panic);
+    common::run(&mut system, 6, 0);
+    assert_eq!(system.get_ram_array()[2][15], 6);
+}
+
+#[test]
+#[should_panic(expected = "RAM file 255 out of range")]
+fn panic_panics() {
+    let mut system = load(ChipVariant::TMS1000, STORE, AccessPolicy::Panic);
+    common::run(&mut system, 5, 0);
+}
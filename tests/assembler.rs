@@ -0,0 +1,281 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Assembler output, run on the core
+//Programs end in a loop on a label, so running extra cycles leaves them where they stopped.
+
+use tms::assembler;
+use tms::compiler;
+use tms::TMS1000::{PowerOnPolicy, SYSTEM, PC_SEQ};
+use tms::variant::ChipVariant;
+
+mod common;
+
+fn run(version : ChipVariant, source : &str) -> SYSTEM {
+    let mut system = common::load(version, source, PowerOnPolicy::Zero);
+    common::run(&mut system, 32, 0);
+    return system;
+}
+
+fn message(version : ChipVariant, source : &str) -> String {
+    let messages = common::messages(assembler::assemble(source.to_string(), version));
+    assert_eq!(messages.len(), 1, "{:?}", messages);
+    return messages[0].clone();
+}
+
+//Branches on YNEC, with Y = 0
+fn conditional(compare : usize) -> String {
+    return format!("  TCY 0\n  YNEC {}\n  BR far\n  TCY 1\nnear: BR near\n.org 0,3\nfar: TCY 2\nloop: BR loop\n", compare);
+}
+
+#[test]
+fn unconditional_branches_load_the_page() {
+    let mut system = run(ChipVariant::TMS1000, "  BR far\n.org 0,3\nfar: TCY 2\nloop: BR loop\n");
+    assert_eq!(system.get_y_reg(), 2);
+    assert_eq!(system.get_pa_reg(), 3);
+}
+
+#[test]
+fn conditional_branches_to_another_page_are_not_taken_on_status_0() {
+    let mut system = run(ChipVariant::TMS1000, &conditional(0));
+    assert_eq!(system.get_y_reg(), 1);
+    assert_eq!(system.get_pa_reg(), 15);
+}
+
+#[test]
+fn conditional_branches_to_another_page_are_taken_on_status_1() {
+    let mut system = run(ChipVariant::TMS1000, &conditional(5));
+    assert_eq!(system.get_y_reg(), 2);
+    assert_eq!(system.get_pa_reg(), 3);
+}
+
+#[test]
+fn the_page_load_goes_before_the_status_test() {
+    let assembled = assembler::assemble_with_listing(conditional(0), ChipVariant::TMS1000).unwrap();
+    let sources: Vec<&str> = assembled.listing.iter().take(4).map(|l| l.source.as_str()).collect();
+    assert_eq!(sources, ["TCY 0", "LDP 3 (inserted)", "YNEC 0", "BR far"]);
+}
+
+#[test]
+fn branches_after_an_untaken_one_reload_their_page() {
+    let source = "  TCY 0\n  YNEC 0\n  BR far\n  TCY 1\n  BR next\n  TCY 3\nnext: TCY 4\nnear: BR near\n.org 0,3\nfar: TCY 2\nloop: BR loop\n";
+    let mut system = run(ChipVariant::TMS1000, source);
+    assert_eq!(system.get_y_reg(), 4);
+    assert_eq!(system.get_pa_reg(), 15);
+    let assembled = assembler::assemble_with_listing(source.to_string(), ChipVariant::TMS1000).unwrap();
+    assert!(assembled.listing.iter().any(|l| l.source == "LDP 15 (inserted)"));
+}
+
+#[test]
+fn labels_on_a_status_test_label_its_page_load() {
+    let mut system = run(ChipVariant::TMS1000, "  TCY 0\n  BR test\n.org 0,2\ntest: YNEC 5\n  BR far\nnear: BR near\n.org 0,3\nfar: TCY 2\nloop: BR loop\n");
+    assert_eq!(system.get_y_reg(), 2);
+    assert_eq!(system.get_pa_reg(), 3);
+}
+
+#[test]
+fn labelled_conditional_branches_to_another_page_are_errors() {
+    let source = "  TCY 0\n  YNEC 0\nback: BR far\n  BR back\n.org 0,3\nfar: TCY 2\n";
+    assert_eq!(message(ChipVariant::TMS1000, source), "<input>:3:7: BR to far needs an LDP, but follows a status test and has a label, so no LDP can serve both paths");
+}
+
+#[test]
+fn branches_into_the_other_chapter() {
+    //Same page number, so only the chapter changes
+    let mut system = run(ChipVariant::TMS1100, "  COMC\n  BR far\n.org 1,15\nfar: TCY 2\nloop: BR loop\n");
+    assert_eq!(system.get_y_reg(), 2);
+    assert_eq!((system.get_ca_reg(), system.get_pa_reg()), (1, 15));
+
+    let mut system = run(ChipVariant::TMS1100, "  TCY 0\n  COMC\n  YNEC 5\n  BR far\n  COMC\nnear: BR near\n.org 1,2\nfar: TCY 2\nloop: BR loop\n");
+    assert_eq!(system.get_y_reg(), 2);
+    assert_eq!((system.get_ca_reg(), system.get_pa_reg()), (1, 2));
+}
+
+#[test]
+fn conditional_branches_into_the_other_chapter_are_not_taken_on_status_0() {
+    //The second COMC puts CB back for the fall-through path
+    let mut system = run(ChipVariant::TMS1100, "  TCY 0\n  COMC\n  YNEC 0\n  BR far\n  COMC\n  TCY 1\nnear: BR near\n.org 1,2\nfar: TCY 2\nloop: BR loop\n");
+    assert_eq!(system.get_y_reg(), 1);
+    assert_eq!((system.get_ca_reg(), system.get_pa_reg()), (0, 15));
+}
+
+#[test]
+fn branches_into_the_other_chapter_need_a_COMC() {
+    let source = "  BR far\n.org 1,15\nfar: TCY 2\n";
+    assert_eq!(message(ChipVariant::TMS1100, source), "<input>:1:3: BR to far in chapter 1 needs a COMC before it");
+    //Two COMCs put CB back
+    let source = "  COMC\n  COMC\n  BR far\n.org 1,15\nfar: TCY 2\n";
+    assert_eq!(message(ChipVariant::TMS1100, source), "<input>:3:3: BR to far in chapter 1 needs a COMC before it");
+    //A label does not start over, since execution can fall into it
+    let mut system = run(ChipVariant::TMS1100, "  COMC\nhere: BR far\n.org 1,15\nfar: TCY 2\nloop: BR loop\n");
+    assert_eq!(system.get_ca_reg(), 1);
+    //An unconditional BR does
+    let source = "  COMC\n  BR far\nback: BR far\n.org 1,15\nfar: BR back\n";
+    assert_eq!(message(ChipVariant::TMS1100, source), "<input>:3:7: BR to far in chapter 1 needs a COMC before it");
+}
+
+#[test]
+fn falling_through_into_the_other_chapter_is_an_error() {
+    let source = "  TCY 0\n  COMC\n  YNEC 0\n  BR far\nnear: BR near\n.org 1,2\nfar: TCY 2\n";
+    assert_eq!(message(ChipVariant::TMS1100, source), "<input>:5:7: BR to near follows an odd number of COMCs, so it would leave chapter 0");
+}
+
+#[test]
+fn subroutines_branch_within_their_page() {
+    //Loops until Y wraps, then returns to the caller
+    let mut system = run(ChipVariant::TMS1000, "  CALL sub\n  TCY 9\nend: BR end\n.org 0,3\nsub: TCY 13\nloop: IYC\n  BR done\n  BR loop\ndone: RETN\n");
+    assert_eq!(system.get_y_reg(), 9);
+}
+
+#[test]
+fn subroutines_cannot_branch_to_another_page() {
+    let source = "  CALL sub\nend: BR end\n.org 0,3\nsub: TCY 1\n  BR far\n.org 0,4\nfar: RETN\n";
+    assert_eq!(message(ChipVariant::TMS1000, source), "<input>:5:3: BR to far on page 4 is inside a subroutine, which can only branch within its own page");
+    //Code reached by a branch from the CALL target is inside it too
+    let source = "  CALL sub\nend: BR end\n.org 0,3\nsub: BR next\n  RETN\nnext: CALL far\n  RETN\n.org 0,4\nfar: RETN\n";
+    assert_eq!(message(ChipVariant::TMS1000, source), "<input>:6:7: CALL to far on page 4 is inside a subroutine, which can only branch within its own page");
+    //Code after the RETN is not
+    let mut system = run(ChipVariant::TMS1000, "  CALL sub\n  BR after\n.org 0,3\nsub: RETN\nafter: BR far\n.org 0,4\nfar: TCY 4\nloop: BR loop\n");
+    assert_eq!(system.get_y_reg(), 4);
+}
+
+//Directives
+
+//Execution order index i on the given page
+fn at(chapter : usize, page : usize, i : usize) -> usize {
+    return (1024 * chapter) + (64 * page) + PC_SEQ[i];
+}
+
+fn op(mnemonic : &str, operand : u8) -> u8 {
+    return compiler::compile_instruction(mnemonic, operand, ChipVariant::TMS1000).unwrap();
+}
+
+#[test]
+fn org_moves_to_the_given_page() {
+    let rom = common::assemble(ChipVariant::TMS1000, "  TCY 1\n.org 0,3\n  TCY 2\n  TCY 3\n");
+    assert_eq!(rom[at(0, 15, 0)], op("TCY", 1));
+    assert_eq!((rom[at(0, 3, 0)], rom[at(0, 3, 1)]), (op("TCY", 2), op("TCY", 3)));
+    let rom = common::assemble(ChipVariant::TMS1100, ".org 1,$2\n  TCY 1\n");
+    assert_eq!(rom[at(1, 2, 0)], compiler::compile_instruction("TCY", 1, ChipVariant::TMS1100).unwrap());
+    assert_eq!(message(ChipVariant::TMS1000, ".org 2,0\n"), "<input>:1:1: No chapter 2, page 0");
+    assert_eq!(message(ChipVariant::TMS1000, ".org 0,16\n"), "<input>:1:1: No chapter 0, page 16");
+    assert_eq!(message(ChipVariant::TMS1000, ".org 1,0\n  TCY 1\n"), "<input>:2:3: Chapter 1 does not exist on the TMS1000");
+}
+
+#[test]
+fn page_moves_to_the_next_unused_page() {
+    //From the reset page on to page 0, in the same chapter
+    let rom = common::assemble(ChipVariant::TMS1000, "  TCY 1\n.page\n  TCY 2\n");
+    assert_eq!((rom[at(0, 15, 0)], rom[at(0, 0, 0)]), (op("TCY", 1), op("TCY", 2)));
+    let rom = common::assemble(ChipVariant::TMS1100, "  TCY 1\n.page\n  TCY 2\n");
+    assert_eq!(rom[at(0, 0, 0)], op("TCY", 2));
+    assert_eq!(rom[at(1, 0, 0)], 0);
+    //Used pages are skipped
+    let rom = common::assemble(ChipVariant::TMS1000, ".org 0,0\n  TCY 1\n.org 0,15\n  TCY 2\n.page\n  TCY 3\n");
+    assert_eq!(rom[at(0, 1, 0)], op("TCY", 3));
+    //Nothing placed yet, so it stays
+    let rom = common::assemble(ChipVariant::TMS1000, ".page\n.page\n  TCY 1\n");
+    assert_eq!(rom[at(0, 15, 0)], op("TCY", 1));
+    let full: String = (0..16).map(|p| format!(".org 0,{}\n  TCY 1\n", p)).collect();
+    assert_eq!(message(ChipVariant::TMS1000, &format!("{}.page\n", full)), "<input>:33:1: Every page of chapter 0 is in use");
+}
+
+#[test]
+fn equ_defines_constants() {
+    let rom = common::assemble(ChipVariant::TMS1000, ".equ FIVE 5\n.equ ALSO FIVE\n  TCY FIVE\n  YNEC ALSO\n.org 0,FIVE\n  TCY 0\n");
+    assert_eq!((rom[at(0, 15, 0)], rom[at(0, 15, 1)], rom[at(0, 5, 0)]), (op("TCY", 5), op("YNEC", 5), op("TCY", 0)));
+    assert_eq!(message(ChipVariant::TMS1000, ".equ N 1\n.equ N 2\n"), "<input>:2:1: Constant N defined more than once");
+    assert_eq!(message(ChipVariant::TMS1000, ".equ 1N 1\n"), "<input>:1:1: Invalid constant name 1N");
+    assert_eq!(message(ChipVariant::TMS1000, ".equ N M\n"), "<input>:1:1: Undefined constant M");
+    assert_eq!(message(ChipVariant::TMS1000, "  TCY N\n"), "<input>:1:3: Undefined symbol N");
+}
+
+#[test]
+fn byte_places_raw_bytes() {
+    let rom = common::assemble(ChipVariant::TMS1000, ".equ N 0x42\n  TCY 1\n.byte 1, 0xFF, 'A', N\n  TCY 2\n");
+    let placed: Vec<u8> = (0..6).map(|i| rom[at(0, 15, i)]).collect();
+    assert_eq!(placed, [op("TCY", 1), 1, 0xFF, 65, 0x42, op("TCY", 2)]);
+    assert_eq!(message(ChipVariant::TMS1000, ".byte 256\n"), "<input>:1:1: Byte 256 out of range (0-255)");
+}
+
+#[test]
+fn fill_places_copies_of_a_byte() {
+    let rom = common::assemble(ChipVariant::TMS1000, ".fill 3, 7\n.fill 2\n  TCY 1\n");
+    let placed: Vec<u8> = (0..6).map(|i| rom[at(0, 15, i)]).collect();
+    assert_eq!(placed, [7, 7, 7, 0, 0, op("TCY", 1)]);
+    assert_eq!(message(ChipVariant::TMS1000, ".fill 65\n"), "<input>:1:1: Page 15 of chapter 0 is full (64 instructions)");
+    assert_eq!(message(ChipVariant::TMS1000, ".fill\n"), "<input>:1:1: Unknown directive or wrong number of arguments: .fill");
+}
+
+#[test]
+fn include_assembles_a_file_in_place() {
+    let assembled = assembler::assemble_file("tests/fixtures/asm/include.asm".to_string(), ChipVariant::TMS1000).unwrap();
+    let placed: Vec<u8> = (0..4).map(|i| assembled.rom[at(0, 15, i)]).collect();
+    assert_eq!(placed, [op("TCY", 3), op("TCY", 1), op("TYA", 0), op("TCY", 2)]);
+    //The listing points into the included file
+    assert_eq!((assembled.listing[1].file.as_str(), assembled.listing[1].line), ("tests/fixtures/asm/included.asm", 2));
+}
+
+#[test]
+fn include_errors() {
+    let messages = common::messages(assembler::assemble_file("tests/fixtures/asm/missing_include.asm".to_string(), ChipVariant::TMS1000));
+    assert_eq!(messages[0], "tests/fixtures/asm/missing_include.asm:2:1: Could not read included file tests/fixtures/asm/missing.asm");
+    let messages = common::messages(assembler::assemble_file("tests/fixtures/asm/recursive.asm".to_string(), ChipVariant::TMS1000));
+    assert!(messages[0].contains(": Includes nested too deeply"), "{}", messages[0]);
+}
+
+//Macros
+
+#[test]
+fn macro_parameters_are_substituted() {
+    let source = ".macro STORE value, address\n  TCY \\address\n  TCMIY \\value\n.endm\n  STORE 5, 3\n  STORE 0x0A, 4\n";
+    let rom = common::assemble(ChipVariant::TMS1000, source);
+    let placed: Vec<u8> = (0..4).map(|i| rom[at(0, 15, i)]).collect();
+    assert_eq!(placed, [op("TCY", 3), op("TCMIY", 5), op("TCY", 4), op("TCMIY", 10)]);
+    //Longer names first, so \count is not taken for \c followed by "ount"
+    let source = ".macro M c, count\n  TCY \\count\n  YNEC \\c\n.endm\n  M 1, 2\n";
+    let rom = common::assemble(ChipVariant::TMS1000, source);
+    assert_eq!((rom[at(0, 15, 0)], rom[at(0, 15, 1)]), (op("TCY", 2), op("YNEC", 1)));
+}
+
+#[test]
+fn macro_local_labels_are_unique_to_each_expansion() {
+    let source = ".macro WAIT\nloop\\@: DYN\n  BR loop\\@\n.endm\n  TCY 2\n  WAIT\n  TCY 3\n  WAIT\n  TCY 9\ndone: BR done\n";
+    let assembled = assembler::assemble_with_listing(source.to_string(), ChipVariant::TMS1000).unwrap();
+    let names: Vec<&str> = assembled.symbols.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["loop_1", "loop_2", "done"]);
+    let mut system = run(ChipVariant::TMS1000, source);
+    assert_eq!(system.get_y_reg(), 9);
+}
+
+#[test]
+fn macros_may_invoke_macros() {
+    let source = ".macro SET value\n  TCMIY \\value\n.endm\n.macro PAIR first, second\n  SET \\first\n  SET \\second\n.endm\nstart: PAIR 1, 2\n  TCY 0\n  PAIR 3, 4\n";
+    let assembled = assembler::assemble_with_listing(source.to_string(), ChipVariant::TMS1000).unwrap();
+    let placed: Vec<u8> = (0..5).map(|i| assembled.rom[at(0, 15, i)]).collect();
+    assert_eq!(placed, [op("TCMIY", 1), op("TCMIY", 2), op("TCY", 0), op("TCMIY", 3), op("TCMIY", 4)]);
+    //A label in front of an invocation labels the first line of the expansion
+    assert_eq!((assembled.symbols[0].name.as_str(), assembled.symbols[0].pc), ("start", PC_SEQ[0]));
+}
+
+#[test]
+fn recursive_macros_are_errors() {
+    let source = ".macro LOOP\n  TCY 1\n  LOOP\n.endm\n  LOOP\n";
+    assert_eq!(message(ChipVariant::TMS1000, source), "<input>:5:3: Macro LOOP nested too deeply (does it invoke itself?)");
+}
+
+#[test]
+fn macro_definition_errors() {
+    assert_eq!(message(ChipVariant::TMS1000, "  TCY 1\n.macro M a\n  TCY \\a\n"), "<input>:2:1: Macro M has no .endm");
+    assert_eq!(message(ChipVariant::TMS1000, ".endm\n"), "<input>:1:1: .endm without .macro");
+    assert_eq!(message(ChipVariant::TMS1000, ".macro M\n.macro N\n.endm\n.endm\n"), "<input>:2:1: Macro definitions cannot be nested");
+    assert_eq!(message(ChipVariant::TMS1000, ".macro M\n.endm\n.macro m\n.endm\n"), "<input>:3:1: Macro M defined more than once");
+    assert_eq!(message(ChipVariant::TMS1000, ".macro TAY\n.endm\n"), "<input>:1:1: Macro TAY has the name of an instruction");
+}
+
+#[test]
+fn macro_argument_counts_are_checked() {
+    let source = ".macro STORE value, address\n  TCY \\address\n  TCMIY \\value\n.endm\n  STORE 5\n";
+    assert_eq!(message(ChipVariant::TMS1000, source), "<input>:5:3: Macro STORE takes 2 arguments, 1 given");
+    let source = ".macro CLEAR\n  CLA\n.endm\n  CLEAR 1\n";
+    assert_eq!(message(ChipVariant::TMS1000, source), "<input>:4:3: Macro CLEAR takes 0 arguments, 1 given");
+}
@@ -0,0 +1,33 @@
+#![allow(non_snake_case)]
+//Control-flow graph of small assembled programs
+
+use tms::assembler;
+use tms::cfg::{self, EdgeKind};
+use tms::variant::ChipVariant;
+
+const SOURCE : &str = "
+start:  TCY 3
+loop:   CALL sub
+        YNEC 5
+        BR loop
+        BR start
+.org 0, 1
+sub:    RETN
+        .byte 0
+";
+
+#[test]
+fn blocks_and_edges() {
+    let rom = assembler::assemble(SOURCE.to_string(), ChipVariant::TMS1000).unwrap();
+    let graph = cfg::build(&rom, ChipVariant::TMS1000);
+    let starts: Vec<usize> = graph.blocks.iter().map(|b| b.start()).collect();
+    //start, loop (the LDP inserted before the CALL), the return site, BR start after BR loop fails, and sub
+    assert_eq!(graph.blocks.len(), 5, "{:?}", starts);
+    let kinds: Vec<EdgeKind> = graph.edges.iter().map(|e| e.kind).collect();
+    for kind in [EdgeKind::Call, EdgeKind::Return, EdgeKind::Branch, EdgeKind::NotTaken] {
+        assert!(kinds.contains(&kind), "{:?} missing from {:?}", kind, graph.edges);
+    }
+    //The .byte after RETN is never reached
+    assert!(!graph.flow.reachable[64 + 1]);
+    assert!(graph.to_dot(&rom, ChipVariant::TMS1000).starts_with("digraph"));
+}
@@ -0,0 +1,53 @@
+#![allow(dead_code)] //each test file uses only some of these
+#![allow(clippy::needless_return)]
+//Systems shared by the integration tests, included with `mod common;`
+//No TMS1000 instruction PLA is bundled, so TMS1000 programs run on tests/fixtures/tms1000_mpla.pla, and
+//TMS1100 programs on Merlin's. Assembled programs use the fixture output PLA, which passes O through.
+
+use tms::assembler;
+use tms::compiler::Diagnostic;
+use tms::TMS1000::{PowerOnPolicy, SYSTEM};
+use tms::variant::ChipVariant;
+
+pub const FIXTURE_OPLA : &str = "tests/fixtures/tms1000_opla.pla";
+
+pub fn ipla(version : ChipVariant) -> &'static str {
+    return match version.is_TMS1100() {
+        true => "ancillary/tms1100_merlin_mpla.pla",
+        false => "tests/fixtures/tms1000_mpla.pla",
+    };
+}
+
+//Assembles a program, with "|" allowed between statements
+pub fn assemble(version : ChipVariant, source : &str) -> Vec<u8> {
+    return match assembler::assemble(source.replace('|', "\n"), version) {
+        Ok(v) => v,
+        Err(e) => panic!("{}: {:?}", source, e),
+    };
+}
+
+//The messages of a build that failed
+pub fn messages<T : std::fmt::Debug>(result : Result<T, Vec<Diagnostic>>) -> Vec<String> {
+    return result.unwrap_err().iter().map(|d| d.to_string()).collect();
+}
+
+//Assembles a program and loads it, ready for its first instruction cycle
+pub fn load(version : ChipVariant, source : &str, policy : PowerOnPolicy) -> SYSTEM {
+    let mut system = SYSTEM::load_system_from_rom(version, assemble(version, source), ipla(version).to_string(), FIXTURE_OPLA.to_string(), policy).unwrap();
+    system.set_logging();
+    return system;
+}
+
+//Merlin (mp3404.bin, TMS1100) on its own PLAs
+pub fn merlin(policy : PowerOnPolicy) -> SYSTEM {
+    let mut system = SYSTEM::load_system_with_policy(ChipVariant::TMS1100, "ancillary/mp3404.bin".to_string(), "ancillary/tms1100_merlin_mpla.pla".to_string(), "ancillary/tms1100_merlin_opla.pla".to_string(), policy).unwrap();
+    system.set_logging();
+    return system;
+}
+
+//Runs `cycles` instruction cycles with the K inputs held at `k`
+pub fn run(system : &mut SYSTEM, cycles : usize, k : u8) {
+    for _ in 0..cycles {
+        system.instruction_cycle_mut(k);
+    }
+}
@@ -0,0 +1,419 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Per-instruction conformance
+//Each case assembles a short program, runs it from reset and checks its effect on registers, RAM,
+//status and outputs. TMS1100 cases run on Merlin's instruction PLA, bundled in ancillary.
+//
+//No TMS1000 PLA is bundled, so the TMS1000 cases run on MANUAL, the data manual's table of the standard
+//instruction set, built into a PLA here rather than read from a file. tests/fixtures/tms1000_mpla.pla,
+//which the other tests use, is checked against the same table, and every instruction the TMS1100 shares
+//is checked against the dumped TMS1100 PLAs in ancillary. A game whose PLA departs from the standard set
+//is still not covered.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use tms::decompiler;
+use tms::TMS1000::{PowerOnPolicy, SYSTEM, PC_SEQ};
+use tms::variant::ChipVariant;
+
+mod common;
+
+#[derive(Clone, Copy, Debug)]
+enum Reg {
+    A,
+    X,
+    Y,
+    S,
+    SL,
+    PA,
+    PB,
+    CB,
+    R,
+    O,
+    M(usize, usize),
+}
+
+//mnemonic tested, program ("|" between statements), K inputs, expected values after the last statement
+type Case = (&'static str, &'static str, u8, &'static [(Reg, usize)]);
+
+const TMS1000_CASES : &[Case] = &[
+    ("COMX", "LDX 1|COMX", 0, &[(Reg::X, 2)]),
+    ("COMX", "LDX 3|COMX", 0, &[(Reg::X, 0)]),
+    ("A8AAC", "TCY 3|TYA|A8AAC", 0, &[(Reg::A, 11), (Reg::S, 0)]),
+    ("A8AAC", "TCY 9|TYA|A8AAC", 0, &[(Reg::A, 1), (Reg::S, 1)]),
+    ("YNEA", "TCY 3|YNEA", 0, &[(Reg::S, 1), (Reg::SL, 1)]),
+    ("YNEA", "TCY 0|YNEA", 0, &[(Reg::S, 0), (Reg::SL, 0)]),
+    ("TAM", "TCY 2|TYA|TAM", 0, &[(Reg::M(0, 2), 2), (Reg::A, 2)]),
+    ("TAMZA", "TCY 7|TYA|TAMZA", 0, &[(Reg::M(0, 7), 7), (Reg::A, 0)]),
+    ("A10AAC", "TCY 3|TYA|A10AAC", 0, &[(Reg::A, 13), (Reg::S, 0)]),
+    ("A6AAC", "TCY 12|TYA|A6AAC", 0, &[(Reg::A, 2), (Reg::S, 1)]),
+    ("DAN", "TCY 5|TYA|DAN", 0, &[(Reg::A, 4), (Reg::S, 1)]),
+    ("DAN", "DAN", 0, &[(Reg::A, 15), (Reg::S, 0)]),
+    ("TKA", "TKA", 9, &[(Reg::A, 9)]),
+    ("KNEZ", "KNEZ", 4, &[(Reg::S, 1)]),
+    ("KNEZ", "KNEZ", 0, &[(Reg::S, 0)]),
+    ("TDO", "TCY 1|YNEA|TCY 5|TYA|TDO", 0, &[(Reg::SL, 1), (Reg::O, 21)]),
+    ("CLO", "TCY 5|TYA|TDO|CLO", 0, &[(Reg::O, 0)]),
+    ("SETR", "TCY 3|SETR", 0, &[(Reg::R, 8)]),
+    ("RSTR", "TCY 3|SETR|TCY 4|SETR|RSTR", 0, &[(Reg::R, 8)]),
+    ("IA", "IA|IA", 0, &[(Reg::A, 2)]),
+    ("LDP", "LDP 5", 0, &[(Reg::PB, 5), (Reg::PA, 15)]),
+    ("TAMIY", "TCY 4|TYA|TAMIY", 0, &[(Reg::M(0, 4), 4), (Reg::Y, 5)]),
+    ("TMA", "TCY 6|TYA|TAM|CLA|TMA", 0, &[(Reg::A, 6)]),
+    ("TMY", "TCY 9|TYA|TCY 2|TAM|TMY", 0, &[(Reg::Y, 9)]),
+    ("TYA", "TCY 7|TYA", 0, &[(Reg::A, 7)]),
+    ("TAY", "TCY 7|TYA|TCY 0|TAY", 0, &[(Reg::Y, 7)]),
+    ("AMAAC", "TCY 9|TYA|TAM|AMAAC", 0, &[(Reg::A, 2), (Reg::S, 1)]),
+    ("MNEZ", "MNEZ", 0, &[(Reg::S, 0)]),
+    ("MNEZ", "TCMIY 3|TCY 0|MNEZ", 0, &[(Reg::S, 1)]),
+    ("SAMAN", "TCY 4|TYA|TCY 0|TCMIY 9|TCY 0|SAMAN", 0, &[(Reg::A, 5), (Reg::S, 1)]),
+    ("SAMAN", "TCY 4|TYA|TCY 0|TCMIY 2|TCY 0|SAMAN", 0, &[(Reg::A, 14), (Reg::S, 0)]),
+    ("IMAC", "TCMIY 15|TCY 0|IMAC", 0, &[(Reg::A, 0), (Reg::S, 1)]),
+    ("ALEM", "TCY 4|TYA|TCY 0|TCMIY 9|TCY 0|ALEM", 0, &[(Reg::S, 1), (Reg::A, 4)]),
+    ("ALEM", "TCY 10|TYA|TCY 0|TCMIY 9|TCY 0|ALEM", 0, &[(Reg::S, 0)]),
+    ("DMAN", "DMAN", 0, &[(Reg::A, 15), (Reg::S, 0)]),
+    ("DMAN", "TCMIY 5|TCY 0|DMAN", 0, &[(Reg::A, 4), (Reg::S, 1)]),
+    ("IYC", "TCY 15|IYC", 0, &[(Reg::Y, 0), (Reg::S, 1)]),
+    ("DYN", "TCY 0|DYN", 0, &[(Reg::Y, 15), (Reg::S, 0)]),
+    ("CPAIZ", "TCY 3|TYA|CPAIZ", 0, &[(Reg::A, 13), (Reg::S, 0)]),
+    ("CPAIZ", "CPAIZ", 0, &[(Reg::A, 0), (Reg::S, 1)]),
+    ("XMA", "TCY 3|TYA|TCY 0|TCMIY 6|TCY 0|XMA", 0, &[(Reg::A, 6), (Reg::M(0, 0), 3)]),
+    ("CLA", "TCY 3|TYA|CLA", 0, &[(Reg::A, 0)]),
+    ("SBIT", "LDX 2|TCY 5|SBIT 0|SBIT 3", 0, &[(Reg::M(2, 5), 9)]),
+    ("RBIT", "TCMIY 15|TCY 0|RBIT 1", 0, &[(Reg::M(0, 0), 13)]),
+    ("TBIT1", "TCMIY 4|TCY 0|TBIT1 2", 0, &[(Reg::S, 1)]),
+    ("TBIT1", "TCMIY 11|TCY 0|TBIT1 2", 0, &[(Reg::S, 0)]),
+    ("LDX", "LDX 3", 0, &[(Reg::X, 3)]),
+    ("TCY", "TCY 11", 0, &[(Reg::Y, 11)]),
+    ("YNEC", "TCY 6|YNEC 6", 0, &[(Reg::S, 0)]),
+    ("YNEC", "TCY 6|YNEC 5", 0, &[(Reg::S, 1)]),
+    ("TCMIY", "TCY 14|TCMIY 5", 0, &[(Reg::M(0, 14), 5), (Reg::Y, 15)]),
+    ("ALEC", "TCY 5|TYA|ALEC 5", 0, &[(Reg::S, 1)]),
+    ("ALEC", "TCY 5|TYA|ALEC 4", 0, &[(Reg::S, 0)]),
+];
+
+const TMS1100_CASES : &[Case] = &[
+    ("MNEA", "TCY 5|TYA|TCY 0|TCMIY 5|TCY 0|MNEA", 0, &[(Reg::S, 0)]),
+    ("MNEA", "TCY 5|TYA|TCY 0|TCMIY 6|TCY 0|MNEA", 0, &[(Reg::S, 1)]),
+    ("ALEM", "TCY 4|TYA|TCY 0|TCMIY 9|TCY 0|ALEM", 0, &[(Reg::S, 1), (Reg::A, 4)]),
+    ("ALEM", "TCY 10|TYA|TCY 0|TCMIY 9|TCY 0|ALEM", 0, &[(Reg::S, 0)]),
+    ("YNEA", "TCY 3|YNEA", 0, &[(Reg::S, 1), (Reg::SL, 1)]),
+    ("YNEA", "TCY 0|YNEA", 0, &[(Reg::S, 0), (Reg::SL, 0)]),
+    ("XMA", "TCY 3|TYA|TCY 0|TCMIY 6|TCY 0|XMA", 0, &[(Reg::A, 6), (Reg::M(0, 0), 3)]),
+    ("DYN", "TCY 0|DYN", 0, &[(Reg::Y, 15), (Reg::S, 0)]),
+    ("IYC", "TCY 15|IYC", 0, &[(Reg::Y, 0), (Reg::S, 1)]),
+    ("AMAAC", "TCY 9|TYA|TAM|AMAAC", 0, &[(Reg::A, 2), (Reg::S, 1)]),
+    ("DMAN", "DMAN", 0, &[(Reg::A, 15), (Reg::S, 0)]),
+    ("DMAN", "TCMIY 5|TCY 0|DMAN", 0, &[(Reg::A, 4), (Reg::S, 1)]),
+    ("TKA", "TKA", 9, &[(Reg::A, 9)]),
+    ("COMX", "LDX 1|COMX", 0, &[(Reg::X, 5)]),
+    ("COMX", "LDX 6|COMX", 0, &[(Reg::X, 2)]),
+    ("TDO", "TCY 1|YNEA|TCY 5|TYA|TDO", 0, &[(Reg::SL, 1), (Reg::O, 21)]),
+    ("COMC", "COMC", 0, &[(Reg::CB, 1)]),
+    ("COMC", "COMC|COMC", 0, &[(Reg::CB, 0)]),
+    ("RSTR", "TCY 3|SETR|TCY 4|SETR|RSTR", 0, &[(Reg::R, 8)]),
+    ("SETR", "TCY 3|SETR", 0, &[(Reg::R, 8)]),
+    ("KNEZ", "KNEZ", 4, &[(Reg::S, 1)]),
+    ("KNEZ", "KNEZ", 0, &[(Reg::S, 0)]),
+    ("LDP", "LDP 5", 0, &[(Reg::PB, 5), (Reg::PA, 15)]),
+    ("TAY", "TCY 7|TYA|TCY 0|TAY", 0, &[(Reg::Y, 7)]),
+    ("TMA", "TCY 6|TYA|TAM|CLA|TMA", 0, &[(Reg::A, 6)]),
+    ("TMY", "TCY 9|TYA|TCY 2|TAM|TMY", 0, &[(Reg::Y, 9)]),
+    ("TYA", "TCY 7|TYA", 0, &[(Reg::A, 7)]),
+    ("TAMDYN", "TCY 3|TYA|TAMDYN", 0, &[(Reg::M(0, 3), 3), (Reg::Y, 2), (Reg::S, 1)]),
+    ("TAMDYN", "TAMDYN", 0, &[(Reg::Y, 15), (Reg::S, 0)]),
+    ("TAMIYC", "TCY 15|TYA|TAMIYC", 0, &[(Reg::M(0, 15), 15), (Reg::Y, 0), (Reg::S, 1)]),
+    ("TAMIYC", "TCY 4|TYA|TAMIYC", 0, &[(Reg::Y, 5), (Reg::S, 0)]),
+    ("TAMZA", "TCY 7|TYA|TAMZA", 0, &[(Reg::M(0, 7), 7), (Reg::A, 0)]),
+    ("TAM", "TCY 2|TYA|TAM", 0, &[(Reg::M(0, 2), 2), (Reg::A, 2)]),
+    ("LDX", "LDX 6", 0, &[(Reg::X, 6)]),
+    ("SBIT", "LDX 6|TCY 5|SBIT 0|SBIT 3", 0, &[(Reg::M(6, 5), 9)]),
+    ("RBIT", "TCMIY 15|TCY 0|RBIT 1", 0, &[(Reg::M(0, 0), 13)]),
+    ("TBIT1", "TCMIY 4|TCY 0|TBIT1 2", 0, &[(Reg::S, 1)]),
+    ("TBIT1", "TCMIY 11|TCY 0|TBIT1 2", 0, &[(Reg::S, 0)]),
+    ("SAMAN", "TCY 4|TYA|TCY 0|TCMIY 9|TCY 0|SAMAN", 0, &[(Reg::A, 5), (Reg::S, 1)]),
+    ("SAMAN", "TCY 4|TYA|TCY 0|TCMIY 2|TCY 0|SAMAN", 0, &[(Reg::A, 14), (Reg::S, 0)]),
+    ("CPAIZ", "TCY 3|TYA|CPAIZ", 0, &[(Reg::A, 13), (Reg::S, 0)]),
+    ("CPAIZ", "CPAIZ", 0, &[(Reg::A, 0), (Reg::S, 1)]),
+    ("IMAC", "TCMIY 15|TCY 0|IMAC", 0, &[(Reg::A, 0), (Reg::S, 1)]),
+    ("MNEZ", "MNEZ", 0, &[(Reg::S, 0)]),
+    ("MNEZ", "TCMIY 3|TCY 0|MNEZ", 0, &[(Reg::S, 1)]),
+    ("TCY", "TCY 11", 0, &[(Reg::Y, 11)]),
+    ("YNEC", "TCY 6|YNEC 6", 0, &[(Reg::S, 0)]),
+    ("YNEC", "TCY 6|YNEC 5", 0, &[(Reg::S, 1)]),
+    ("TCMIY", "TCY 14|TCMIY 5", 0, &[(Reg::M(0, 14), 5), (Reg::Y, 15)]),
+    ("CLA", "TCY 3|TYA|CLA", 0, &[(Reg::A, 0), (Reg::S, 1)]),
+];
+
+//Tested by their own functions below
+const CONTROL : &[&str] = &["BR", "CALL", "RETN"];
+
+//PLA output lines, from bit 0, in the order the core decodes them
+const MICROINSTRUCTIONS : [&str; 16] = ["STO", "CKM", "CKP", "YTP", "MTP", "ATN", "NATN", "MTN", "15TN", "CKN", "NE", "C8", "CIN", "AUTA", "AUTY", "STSL"];
+//Lines the PLA drives low when active
+const ACTIVE_LOW : u32 = 0b0001001111111100;
+
+//The TMS1000 standard instruction set as the data manual tabulates it: first and last opcode, mnemonic
+//and microinstructions. The fixed instructions (COMX, TDO, CLO, RSTR, SETR, RETN, LDP, SBIT, RBIT, LDX,
+//BR and CALL) are decoded outside the PLA and have none.
+const MANUAL : &[(u8, u8, &str, &[&str])] = &[
+    (0x01, 0x01, "A8AAC", &["CKP", "ATN", "C8", "AUTA"]),
+    (0x02, 0x02, "YNEA", &["YTP", "ATN", "NE", "STSL"]),
+    (0x03, 0x03, "TAM", &["STO"]),
+    (0x04, 0x04, "TAMZA", &["STO", "AUTA"]),
+    (0x05, 0x05, "A10AAC", &["CKP", "ATN", "C8", "AUTA"]),
+    (0x06, 0x06, "A6AAC", &["CKP", "ATN", "C8", "AUTA"]),
+    (0x07, 0x07, "DAN", &["CKP", "ATN", "CIN", "C8", "AUTA"]),
+    (0x08, 0x08, "TKA", &["CKP", "AUTA"]),
+    (0x09, 0x09, "KNEZ", &["CKP", "NE"]),
+    (0x0E, 0x0E, "IA", &["ATN", "CIN", "AUTA"]),
+    (0x20, 0x20, "TAMIY", &["STO", "YTP", "CIN", "AUTY"]),
+    (0x21, 0x21, "TMA", &["MTP", "AUTA"]),
+    (0x22, 0x22, "TMY", &["MTP", "AUTY"]),
+    (0x23, 0x23, "TYA", &["YTP", "AUTA"]),
+    (0x24, 0x24, "TAY", &["ATN", "AUTY"]),
+    (0x25, 0x25, "AMAAC", &["MTP", "ATN", "C8", "AUTA"]),
+    (0x26, 0x26, "MNEZ", &["MTP", "NE"]),
+    (0x27, 0x27, "SAMAN", &["MTP", "NATN", "CIN", "C8", "AUTA"]),
+    (0x28, 0x28, "IMAC", &["MTP", "CIN", "C8", "AUTA"]),
+    (0x29, 0x29, "ALEM", &["MTP", "NATN", "CIN", "C8"]),
+    (0x2A, 0x2A, "DMAN", &["MTP", "15TN", "C8", "AUTA"]),
+    (0x2B, 0x2B, "IYC", &["YTP", "CIN", "C8", "AUTY"]),
+    (0x2C, 0x2C, "DYN", &["YTP", "15TN", "C8", "AUTY"]),
+    (0x2D, 0x2D, "CPAIZ", &["NATN", "CIN", "C8", "AUTA"]),
+    (0x2E, 0x2E, "XMA", &["MTP", "STO", "AUTA"]),
+    (0x2F, 0x2F, "CLA", &["AUTA"]),
+    (0x38, 0x3B, "TBIT1", &["CKP", "CKN", "MTP", "NE"]),
+    (0x40, 0x4F, "TCY", &["CKP", "AUTY"]),
+    (0x50, 0x5F, "YNEC", &["YTP", "CKN", "NE"]),
+    (0x60, 0x6F, "TCMIY", &["CKM", "YTP", "CIN", "AUTY"]),
+    (0x70, 0x7F, "ALEC", &["CKP", "NATN", "CIN", "C8"]),
+];
+
+//The TMS1100 adds its constants with CIN (A1AAC is 0 plus the carry in) and clears A the same way
+const TMS1100_CONSTANTS : &[&str] = &["A8AAC", "A10AAC", "A6AAC", "CLA"];
+
+fn microinstructions(names : &[&str]) -> u32 {
+    return names.iter().map(|name| 1 << MICROINSTRUCTIONS.iter().position(|m| m == name).unwrap()).sum();
+}
+
+//MANUAL as the raw PLA the core reads
+fn manual_pla() -> HashMap<u32, u32> {
+    let mut pla = HashMap::new();
+    for &(first, last, _, names) in MANUAL.iter() {
+        for opcode in first..=last {
+            pla.insert(opcode as u32, microinstructions(names) ^ ACTIVE_LOW);
+        }
+    }
+    return pla;
+}
+
+fn read_pla(filename : &str) -> HashMap<u32, u32> {
+    return SYSTEM::parse_PLA(&fs::read_to_string(filename).unwrap()).unwrap();
+}
+
+//The names of the microinstructions a raw PLA output selects
+fn decoded(raw : Option<&u32>) -> Vec<&'static str> {
+    let lines = raw.map(|r| r ^ ACTIVE_LOW).unwrap_or(0);
+    return MICROINSTRUCTIONS.iter().enumerate().filter(|(i, _)| (lines >> i) & 1 == 1).map(|(_, name)| *name).collect();
+}
+
+fn load(version : ChipVariant, source : &str) -> SYSTEM {
+    let mut system = match version.is_TMS1100() {
+        true => common::load(version, source, PowerOnPolicy::Zero),
+        false => {
+            let mut system = SYSTEM::from_tables(version, common::assemble(version, source), manual_pla(), read_pla(common::FIXTURE_OPLA), PowerOnPolicy::Zero).unwrap();
+            system.set_logging();
+            system
+        },
+    };
+    system.instruction_cycle_mut(0); //fetches the first instruction
+    return system;
+}
+
+fn read(system : &mut SYSTEM, reg : Reg) -> usize {
+    return match reg {
+        Reg::A => system.get_acc_reg() as usize,
+        Reg::X => system.get_x_reg(),
+        Reg::Y => system.get_y_reg(),
+        Reg::S => system.get_status() as usize,
+        Reg::SL => system.get_sl_reg() as usize,
+        Reg::PA => system.get_pa_reg() as usize,
+        Reg::PB => system.get_pb_reg() as usize,
+        Reg::CB => system.get_cb_reg(),
+        Reg::R => system.get_r_outputs_u32() as usize,
+        Reg::O => system.get_o_outputs() as usize,
+        Reg::M(x, y) => system.get_ram_array()[x][y] as usize,
+    };
+}
+
+fn run_cases(version : ChipVariant, cases : &[Case]) {
+    for (mnemonic, source, k, expected) in cases.iter() {
+        let mut system = load(version, source);
+        common::run(&mut system, source.split('|').count(), *k);
+        for (reg, value) in expected.iter() {
+            assert_eq!(read(&mut system, *reg), *value, "{} {}: {:?} after \"{}\"", version, mnemonic, reg, source);
+        }
+    }
+}
+
+//Every mnemonic the decompiler knows for the family has a case
+fn assert_all_covered(version : ChipVariant, cases : &[Case], extra : &[String]) {
+    let covered: HashSet<String> = cases.iter().map(|c| c.0.to_string()).chain(CONTROL.iter().map(|v| v.to_string())).chain(extra.iter().cloned()).collect();
+    for opcode in 0..=255u8 {
+        let mnemonic = decompiler::decodeinstruction(opcode, version);
+        let name = mnemonic.split_whitespace().next().unwrap();
+        assert!(covered.contains(name), "{} opcode {:#04x} ({}) has no conformance case", version, opcode, mnemonic);
+    }
+}
+
+#[test]
+fn tms1000_instructions() {
+    run_cases(ChipVariant::TMS1000, TMS1000_CASES);
+    assert_all_covered(ChipVariant::TMS1000, TMS1000_CASES, &[]);
+}
+
+#[test]
+fn tms1100_instructions() {
+    run_cases(ChipVariant::TMS1100, TMS1100_CASES);
+    let mut constants: Vec<String> = Vec::new();
+    for n in 1..=15 {
+        let source = format!("TCY 3|TYA|A{}AAC", n);
+        let mut system = load(ChipVariant::TMS1100, &source);
+        common::run(&mut system, 3, 0);
+        assert_eq!(read(&mut system, Reg::A), (3 + n) % 16, "{}", source);
+        assert_eq!(read(&mut system, Reg::S), ((3 + n) > 15) as usize, "{}", source);
+        constants.push(format!("A{}AAC", n));
+    }
+    assert_all_covered(ChipVariant::TMS1100, TMS1100_CASES, &constants);
+}
+
+#[test]
+fn manual_names_the_decompiler_mnemonics() {
+    for &(first, last, mnemonic, _) in MANUAL.iter() {
+        for opcode in first..=last {
+            let decompiled = decompiler::decodeinstruction(opcode, ChipVariant::TMS1000);
+            assert_eq!(decompiled.split_whitespace().next(), Some(mnemonic), "opcode {:#04x}", opcode);
+        }
+    }
+}
+
+#[test]
+fn fixture_pla_is_the_manual_table() {
+    let fixture = read_pla(common::ipla(ChipVariant::TMS1000));
+    let manual = manual_pla();
+    for opcode in 0..=255u32 {
+        assert_eq!(decoded(fixture.get(&opcode)), decoded(manual.get(&opcode)), "opcode {:#04x}", opcode);
+    }
+}
+
+//The dumped PLAs are the only real ones bundled; wherever the TMS1100 has an instruction of the same
+//name, its microinstructions must be the manual's
+#[test]
+fn manual_agrees_with_the_dumped_TMS1100_plas() {
+    let mut compared = 0;
+    for filename in ["ancillary/tms1100_merlin_mpla.pla", "ancillary/tms1100_common1_micro.pla"] {
+        let dumped = read_pla(filename);
+        for &(_, _, mnemonic, names) in MANUAL.iter().filter(|entry| !TMS1100_CONSTANTS.contains(&entry.2)) {
+            for opcode in 0..=255u8 {
+                if decompiler::decodeinstruction(opcode, ChipVariant::TMS1100).split_whitespace().next() == Some(mnemonic) {
+                    let mut expected = names.to_vec();
+                    expected.sort_by_key(|name| MICROINSTRUCTIONS.iter().position(|m| m == name));
+                    assert_eq!(decoded(dumped.get(&(opcode as u32))), expected, "{} {} (TMS1100 opcode {:#04x})", filename, mnemonic, opcode);
+                    compared += 1;
+                }
+            }
+        }
+    }
+    assert!(compared > 40, "only {} opcodes compared", compared);
+}
+
+const FAMILIES : [ChipVariant; 2] = [ChipVariant::TMS1000, ChipVariant::TMS1100];
+
+#[test]
+fn br_taken_on_status() {
+    for version in FAMILIES {
+        let mut system = load(version, "BR skip|TCY 1|skip: TCY 2");
+        common::run(&mut system, 2, 0);
+        assert_eq!(system.get_y_reg(), 2, "{}", version);
+    }
+}
+
+//Status cleared by a comparison lasts for the next instruction only: a BR that fails on it sets it
+//back to 1, so the BR after is taken
+#[test]
+fn status_after_failed_br() {
+    for version in FAMILIES {
+        let mut system = load(version, "TCY 0|YNEC 0|BR fail|BR ok|fail: TCY 1|ok: TCY 2");
+        common::run(&mut system, 2, 0);
+        assert_eq!(system.get_status(), 0, "{}", version);
+        common::run(&mut system, 1, 0);
+        assert_eq!(system.get_status(), 1, "{}: status after the failed BR", version);
+        common::run(&mut system, 2, 0);
+        assert_eq!(system.get_y_reg(), 2, "{}: second BR not taken", version);
+
+        let mut system = load(version, "TCY 0|YNEC 0|TCY 1|BR ok|TCY 3|ok: TCY 2");
+        common::run(&mut system, 5, 0);
+        assert_eq!(system.get_y_reg(), 2, "{}: status lasted past the next instruction", version);
+    }
+}
+
+#[test]
+fn call_and_return() {
+    for version in FAMILIES {
+        let mut system = load(version, "CALL sub|TCY 7|.org 0,3|sub: TCY 5|RETN");
+        common::run(&mut system, 2, 0); //LDP inserted by the assembler, CALL
+        assert_eq!(system.get_cl_reg(), 1, "{}", version);
+        assert_eq!((system.get_pa_reg(), system.get_pb_reg()), (3, 15), "{}: PA and PB swapped", version);
+        assert_eq!(system.get_sr_reg(), PC_SEQ[2], "{}: SR holds the return address", version);
+        common::run(&mut system, 2, 0); //TCY 5, RETN
+        assert_eq!(system.get_cl_reg(), 0, "{}", version);
+        assert_eq!((system.get_pa_reg(), system.get_pc_reg()), (15, PC_SEQ[2]), "{}", version);
+        common::run(&mut system, 1, 0);
+        assert_eq!(system.get_y_reg(), 7, "{}", version);
+    }
+}
+
+#[test]
+fn call_not_taken() {
+    for version in FAMILIES {
+        let mut system = load(version, "YNEC 0|CALL sub|TCY 3|sub: TCY 5");
+        common::run(&mut system, 2, 0);
+        assert_eq!(system.get_cl_reg(), 0, "{}", version);
+        assert_eq!(system.get_status(), 1, "{}", version);
+        common::run(&mut system, 1, 0);
+        assert_eq!(system.get_y_reg(), 3, "{}", version);
+    }
+}
+
+//With CL set, CALL only jumps: it keeps SR and CSL, and loads PB from PA, so the RETN that follows
+//goes back to the first caller's PC on the subroutine's page
+#[test]
+fn call_inside_call() {
+    for version in FAMILIES {
+        let mut system = load(version, "CALL sub|TCY 7|.org 0,3|sub: LDP 9|CALL inner|TCY 1|inner: TCY 5|RETN");
+        common::run(&mut system, 2, 0);
+        let return_pc = system.get_sr_reg();
+        common::run(&mut system, 2, 0); //LDP 9, CALL inner
+        assert_eq!(system.get_cl_reg(), 1, "{}", version);
+        assert_eq!((system.get_pa_reg(), system.get_pb_reg()), (3, 3), "{}: PB loaded from PA", version);
+        assert_eq!(system.get_sr_reg(), return_pc, "{}: SR kept", version);
+        common::run(&mut system, 1, 0);
+        assert_eq!(system.get_y_reg(), 5, "{}: inner not reached", version);
+        common::run(&mut system, 1, 0); //RETN
+        assert_eq!(system.get_cl_reg(), 0, "{}", version);
+        assert_eq!((system.get_pa_reg(), system.get_pc_reg()), (3, return_pc), "{}", version);
+    }
+}
+
+//Outside a subroutine RETN only loads PA from PB; execution carries on in sequence on the new page
+#[test]
+fn retn_with_call_latch_clear() {
+    for version in FAMILIES {
+        let mut system = load(version, "LDP 5|RETN");
+        common::run(&mut system, 2, 0);
+        assert_eq!(system.get_cl_reg(), 0, "{}", version);
+        assert_eq!((system.get_pa_reg(), system.get_pc_reg()), (5, PC_SEQ[2]), "{}", version);
+    }
+}
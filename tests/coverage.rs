@@ -0,0 +1,44 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Coverage recorded while running Merlin (TMS1100)
+
+use tms::coverage::Coverage;
+use tms::TMS1000::PowerOnPolicy;
+
+mod common;
+
+fn run(cycles : usize) -> Coverage {
+    let mut system = common::merlin(PowerOnPolicy::Sentinel);
+    system.set_coverage(true);
+    common::run(&mut system, cycles, 0);
+    return system.get_coverage().unwrap();
+}
+
+#[test]
+fn counts_executions_and_branches() {
+    let coverage = run(2000);
+    assert_eq!(coverage.executions[64 * 15], 1, "reset vector fetched once");
+    assert_eq!(coverage.executions.iter().sum::<u64>(), 2000);
+    let branches: u64 = coverage.taken.iter().sum::<u64>() + coverage.not_taken.iter().sum::<u64>();
+    assert!(branches > 0);
+}
+
+#[test]
+fn disabled_by_default() {
+    let mut system = common::merlin(PowerOnPolicy::Sentinel);
+    common::run(&mut system, 1, 0);
+    assert!(system.get_coverage().is_none());
+}
+
+#[test]
+fn files_roundtrip_and_merge() {
+    let coverage = run(500);
+    let read = Coverage::from_text(&coverage.to_text()).unwrap();
+    assert_eq!(read, coverage);
+    let mut merged = read.clone();
+    merged.merge(&coverage);
+    for index in 0..2048 {
+        assert_eq!(merged.executions[index], 2 * coverage.executions[index]);
+        assert_eq!(merged.taken[index], 2 * coverage.taken[index]);
+    }
+}
@@ -0,0 +1,75 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Definedness tracking
+//Every program runs from a zeroed power-on, so only the tracking, not the values, tells a stray read
+//apart. TMS1000 programs run on the fixture PLA also used by the conformance tests.
+
+use tms::TMS1000::{PowerOnPolicy, UndefinedRead};
+use tms::variant::ChipVariant;
+
+mod common;
+
+//Runs the program ("|" between statements) to its end and returns what was read undefined
+fn run(source : &str, tracking : bool) -> Vec<UndefinedRead> {
+    let mut system = common::load(ChipVariant::TMS1000, source, PowerOnPolicy::Zero);
+    system.set_definedness_tracking(tracking);
+    common::run(&mut system, source.split('|').count() + 1, 0);
+    return system.get_undefined_reads();
+}
+
+fn sources(reads : &[UndefinedRead]) -> Vec<&str> {
+    return reads.iter().map(|r| r.source.as_str()).collect();
+}
+
+#[test]
+fn unwritten_ram_word() {
+    let reads = run("LDX 0|TCY 3|TMA", true);
+    assert_eq!(sources(&reads), ["MTP: RAM 0, 3"]);
+    //TMA is the third instruction from the reset vector
+    assert_eq!((reads[0].chapter, reads[0].page, reads[0].pc), (0, 15, 3));
+    assert_eq!(reads[0].rom_address, (64 * 15) + 3);
+}
+
+#[test]
+fn unwritten_register() {
+    assert_eq!(sources(&run("TYA", true)), ["YTP: Y register"]);
+    assert_eq!(sources(&run("TCY 1|SETR|TAM", true)), ["STO: Accumulator", "STO: X register"]);
+}
+
+#[test]
+fn address_from_undefined_x() {
+    let reads = run("TCY 3|TCMIY 5|TCY 3|TMA", true);
+    assert!(sources(&reads).contains(&"MTP: X register"), "{:?}", sources(&reads));
+    assert!(sources(&reads).contains(&"MTP: RAM 0, 3"), "a word written through an undefined X is itself undefined");
+}
+
+#[test]
+fn address_from_undefined_y() {
+    assert_eq!(sources(&run("LDX 0|CLA|TAM", true)), ["STO: Y register"]);
+}
+
+#[test]
+fn undefined_values_propagate() {
+    //A is computed from the undefined Y, stored, and read back
+    assert_eq!(sources(&run("TYA|LDX 0|TCY 0|TAM|TMA", true)), ["YTP: Y register", "STO: Accumulator", "MTP: RAM 0, 0"]);
+}
+
+#[test]
+fn defined_paths_raise_nothing() {
+    assert!(run("LDX 0|TCY 3|TCMIY 5|TCY 3|TMA|TAMIY|TYA|SETR", true).is_empty());
+    assert!(run("LDX 1|TCY 0|CLA|TAM|TMA|A6AAC|YNEA|TDO", true).is_empty());
+}
+
+#[test]
+fn reads_are_drained() {
+    let mut system = common::load(ChipVariant::TMS1000, "TYA", PowerOnPolicy::Zero);
+    system.set_definedness_tracking(true);
+    common::run(&mut system, 2, 0);
+    assert_eq!(system.get_undefined_reads().len(), 1);
+    assert!(system.get_undefined_reads().is_empty());
+}
+
+#[test]
+fn nothing_is_recorded_without_tracking() {
+    assert!(run("LDX 0|TCY 3|TMA|TYA", false).is_empty());
+}
@@ -0,0 +1,140 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Compiler and assembler error reporting: file:line:column messages, every bad line in one run, and
+//no output from the binaries unless the whole file builds
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tms::assembler;
+use tms::compiler;
+use tms::variant::ChipVariant;
+
+mod common;
+
+const BAD_COMPILER_SOURCE : &str = "0 15 00 : TCY 3
+0 15 01 : FOO
+0 16 00 : TCY 1
+0 15 02 : TCY 16
+0 15 00 : TAY
+garbage here
+0 15 03 (05) : TCY 1
+loop: 0 15 04 : BR 3 (9)
+0 15 05 : TAY 1
+";
+
+const COMPILER_MESSAGES : &[&str] = &[
+    "2:11: Unknown instruction FOO",
+    "3:1: Address 0 16 0 out of range",
+    "4:15: Operand 16 of TCY out of range (0-15)",
+    "5:1: Address 0 15 0 already assigned on line 1",
+    "6:1: Could not parse \"garbage here\"",
+    "7:10: PC 3 is execution index 2, not 5",
+    "8:23: Annotation (9) does not match the execution index of 3",
+    "9:15: TAY does not take an operand (got 1)",
+];
+
+const BAD_ASSEMBLER_SOURCE : &str = "  TCY 3
+  FOO
+  TCY 16
+  BR nowhere
+  TAY 1
+fine: TCY 1
+  .byte 300
+  what is this
+.org 5,0
+.bogus
+  TCY 2
+";
+
+const ASSEMBLER_MESSAGES : &[&str] = &[
+    "8:3: Could not parse \"what is this\"",
+    "9:1: No chapter 5, page 0",
+    "10:1: Unknown directive or wrong number of arguments: .bogus",
+];
+
+//Statements are only checked once the whole file parses
+const ASSEMBLER_STATEMENT_MESSAGES : &[&str] = &[
+    "2:3: Unknown instruction FOO",
+    "3:3: Operand 16 of TCY out of range (0-15)",
+    "4:3: Undefined symbol nowhere",
+    "5:3: TAY does not take an operand",
+    "7:3: Byte 300 out of range (0-255)",
+];
+
+fn prefixed(file : &str, messages : &[&str]) -> Vec<String> {
+    return messages.iter().map(|m| format!("{}:{}", file, m)).collect();
+}
+
+//A fresh directory for one test's files
+fn scratch(name : &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("tms-diagnostics-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    return directory;
+}
+
+#[test]
+fn compiler_reports_every_bad_line() {
+    assert_eq!(common::messages(compiler::compile(BAD_COMPILER_SOURCE.to_string(), ChipVariant::TMS1000)), prefixed("<input>", COMPILER_MESSAGES));
+}
+
+#[test]
+fn assembler_reports_every_bad_line() {
+    assert_eq!(common::messages(assembler::assemble(BAD_ASSEMBLER_SOURCE.to_string(), ChipVariant::TMS1000)), prefixed("<input>", ASSEMBLER_MESSAGES));
+    let parsed: String = BAD_ASSEMBLER_SOURCE.lines().take(7).map(|l| format!("{}\n", l)).collect();
+    assert_eq!(common::messages(assembler::assemble(parsed, ChipVariant::TMS1000)), prefixed("<input>", ASSEMBLER_STATEMENT_MESSAGES));
+}
+
+#[test]
+fn columns_count_from_the_start_of_the_line() {
+    assert_eq!(common::messages(compiler::compile("name:   0 15 00 : TCY 99\n".to_string(), ChipVariant::TMS1000)), ["<input>:1:23: Operand 99 of TCY out of range (0-15)"]);
+    assert_eq!(common::messages(assembler::assemble("\tloop:  YNEC 99\n".to_string(), ChipVariant::TMS1000)), ["<input>:1:9: Operand 99 of YNEC out of range (0-15)"]);
+}
+
+#[test]
+fn files_are_named() {
+    assert_eq!(common::messages(compiler::compile_file("tests/fixtures/no such file".to_string(), ChipVariant::TMS1000)), ["tests/fixtures/no such file:0:0: Problem opening or reading file"]);
+    assert_eq!(common::messages(assembler::assemble_file("tests/fixtures/no such file".to_string(), ChipVariant::TMS1000)), ["tests/fixtures/no such file:0:0: Problem opening or reading file"]);
+}
+
+//Runs a binary on a source file, returning the file name, standard output and whether it wrote the ROM
+fn build(binary : &str, directory : &Path, source : &str) -> (String, String, bool) {
+    let input = directory.join("source");
+    let output = directory.join("rom.bin");
+    fs::write(&input, source).unwrap();
+    let _ = fs::remove_file(&output);
+    let run = Command::new(binary).args(["1000", input.to_str().unwrap(), output.to_str().unwrap()]).output().unwrap();
+    return (input.display().to_string(), String::from_utf8(run.stdout).unwrap(), output.exists());
+}
+
+#[test]
+fn compile_writes_nothing_on_error() {
+    let directory = scratch("compile");
+    let (input, stdout, written) = build(env!("CARGO_BIN_EXE_compile"), &directory, BAD_COMPILER_SOURCE);
+    let mut expected = prefixed(&input, COMPILER_MESSAGES);
+    expected.push(format!("{} errors, no output written", COMPILER_MESSAGES.len()));
+    assert_eq!(stdout.lines().collect::<Vec<&str>>(), expected);
+    assert!(!written);
+
+    let (_, stdout, written) = build(env!("CARGO_BIN_EXE_compile"), &directory, "0 15 00 : TCY 3\n");
+    assert_eq!(stdout, "Success!\n");
+    assert!(written);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn assemble_writes_nothing_on_error() {
+    let directory = scratch("assemble");
+    let (input, stdout, written) = build(env!("CARGO_BIN_EXE_assemble"), &directory, BAD_ASSEMBLER_SOURCE);
+    let mut expected = prefixed(&input, ASSEMBLER_MESSAGES);
+    expected.push(format!("{} errors, no output written", ASSEMBLER_MESSAGES.len()));
+    assert_eq!(stdout.lines().collect::<Vec<&str>>(), expected);
+    assert!(!written);
+
+    let (_, stdout, written) = build(env!("CARGO_BIN_EXE_assemble"), &directory, "  TCY 3\n");
+    assert_eq!(stdout, "Success!\n");
+    assert!(written);
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,5 @@
+; Includes a file next to it
+.equ START 3
+  TCY START
+.include "included.asm"
+  TCY 2
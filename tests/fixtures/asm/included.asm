@@ -0,0 +1,3 @@
+; Included by include.asm
+  TCY 1
+  TYA
@@ -0,0 +1,2 @@
+  TCY 1
+.include missing.asm
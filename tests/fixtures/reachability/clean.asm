@@ -0,0 +1,9 @@
+; Calls into another page and returns, with no findings
+start:  TCY 3
+loop:   CALL sub
+        YNEC 5
+        BR loop
+        BR start
+.org 0,1
+sub:    TCMIY 1
+        RETN
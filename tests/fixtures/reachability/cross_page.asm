@@ -0,0 +1,7 @@
+; The BR 0 changes page on a page buffer loaded in the block before it
+        LDP 3
+target: TCY 1
+        BR 0
+.org 0,3
+        TCY 2
+        BR target
@@ -0,0 +1,5 @@
+; Skips over two bytes, and ends in a loop; everything else is free
+        BR skip
+        .byte 0x0A, 0x0B
+skip:   TCY 1
+loop:   BR loop
@@ -0,0 +1,7 @@
+; sub is entered by CALL, and also by falling through from the TCY before it
+start:  CALL sub
+        BR before
+.org 0,2
+before: TCY 1
+sub:    TCY 2
+        RETN
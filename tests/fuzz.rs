@@ -0,0 +1,85 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Robustness: arbitrary ROMs, PLAs and source text must never panic
+//The cargo-fuzz targets in fuzz/ drive the same entry points for longer runs.
+
+use proptest::prelude::*;
+
+use tms::assembler;
+use tms::compiler;
+use tms::TMS1000::{AccessPolicy, PowerOnPolicy, SYSTEM};
+use tms::variant::ChipVariant;
+
+const VARIANTS : [ChipVariant; 6] = [ChipVariant::TMS1000, ChipVariant::TMS1070, ChipVariant::TMS1100, ChipVariant::TMS1200, ChipVariant::TMS1270, ChipVariant::TMS1300];
+
+fn policy() -> impl Strategy<Value = PowerOnPolicy> {
+    return prop_oneof![Just(PowerOnPolicy::Zero), Just(PowerOnPolicy::Sentinel), any::<u64>().prop_map(PowerOnPolicy::Random)];
+}
+
+//Source lines built from real mnemonics, directives and operands, so the parsers get past their first check
+fn source_line() -> impl Strategy<Value = String> {
+    let words = prop_oneof![
+        Just("TCY"), Just("LDX"), Just("LDP"), Just("BR"), Just("CALL"), Just("SBIT"), Just("A8AAC"), Just("A16AAC"), Just("RETN"),
+        Just(".org"), Just(".page"), Just(".equ"), Just(".byte"), Just(".fill"), Just(".macro"), Just(".endm"), Just(".BYTE"),
+        Just("label:"), Just("label"), Just("\\@"), Just(";"), Just(":"), Just(","), Just("'"), Just("'a'"), Just("$"), Just("0x"), Just("%"),
+        Just("0"), Just("1"), Just("15"), Just("63"), Just("64"), Just("255"), Just("99999999999999999999"), Just("-1"), Just("(4)"), Just("(")
+    ];
+    return proptest::collection::vec(words, 0..8).prop_map(|w| w.join(" "));
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn core_never_panics(
+        variant in 0..VARIANTS.len(),
+        rom in proptest::collection::vec(any::<u8>(), 0..2200),
+        ipla in proptest::collection::hash_map(0u32..256, any::<u32>(), 0..256),
+        opla in proptest::collection::hash_map(0u32..32, any::<u32>(), 0..32),
+        policy in policy(),
+        k in proptest::collection::vec(0u8..16, 300),
+        definedness in any::<bool>(),
+        init in prop_oneof![0u32..4, any::<u32>()],
+        halt in any::<bool>(),
+    ) {
+        let mut system = SYSTEM::from_tables(VARIANTS[variant], rom, ipla, opla, policy).unwrap();
+        if halt {
+            system.set_access_policy(AccessPolicy::Halt);
+        }
+        system.set_logging();
+        system.set_definedness_tracking(definedness);
+        system.set_coverage(true);
+        system.set_profiling(true);
+        for (cycle, &input) in k.iter().enumerate() {
+            if cycle == 150 && init > 0 {
+                system.set_init(init);
+            }
+            system.instruction_cycle_mut(input);
+            system.get_o_outputs();
+            system.get_r_outputs_u32();
+        }
+    }
+
+    #[test]
+    fn parse_PLA_never_panics(data in "\\PC{0,200}") {
+        let _ = SYSTEM::parse_PLA(&data);
+    }
+
+    #[test]
+    fn parse_PLA_terms_never_panic(terms in proptest::collection::vec("[-01]{1,24} [01]{1,40}", 0..8)) {
+        let _ = SYSTEM::parse_PLA(&terms.join("\n"));
+    }
+
+    #[test]
+    fn compiler_never_panics(data in "\\PC{0,200}", variant in 0..VARIANTS.len()) {
+        let _ = compiler::compile(data.clone(), VARIANTS[variant]);
+        let _ = assembler::assemble(data, VARIANTS[variant]);
+    }
+
+    #[test]
+    fn compiler_lines_never_panic(lines in proptest::collection::vec(source_line(), 0..12), variant in 0..VARIANTS.len()) {
+        let source = lines.join("\n");
+        let _ = compiler::compile(source.clone(), VARIANTS[variant]);
+        let _ = assembler::assemble(source, VARIANTS[variant]);
+    }
+}
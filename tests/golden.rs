@@ -0,0 +1,132 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Golden output regression tests
+//Each bundled ROM is booted with zeroed RAM and played with a script of key presses. Every change of
+//the R or O outputs is recorded as "cycle R O" (hexadecimal) and compared with the checked-in file in
+//tests/golden. After a deliberate change to the core, rewrite the files with
+//    UPDATE_GOLDEN=1 cargo test --test golden
+//and review the diff.
+//No TMS1000 instruction PLA is bundled, so simon.bin and mp3300.bin use the conformance fixture.
+//Only Merlin has its own output PLA. For the other games O is the O register as TDO left it, before
+//any output PLA, since dummypla.pla would only scramble it.
+//Keys sit in a matrix: a held key feeds its K bit back while its select line is high. The presses are
+//the ones seen to move each game on (the first Simon round, Merlin's LEDs, the Fair display digits);
+//what the games call those buttons is not checked here.
+
+use std::fs;
+
+use tms::TMS1000::{PowerOnPolicy, SYSTEM};
+use tms::variant::ChipVariant;
+
+//The outputs that select a key matrix row
+#[derive(Clone, Copy)]
+enum Select {
+    R,
+    //O output lines, after the output PLA
+    O,
+}
+
+//(select line, K bit) of a key
+type Key = (usize, u8);
+
+//(instruction cycles, keys held for them)
+type Script = &'static [(u64, &'static [Key])];
+
+struct Game {
+    version : ChipVariant,
+    rom : &'static str,
+    ipla : &'static str,
+    //None where no output PLA for the game is bundled
+    opla : Option<&'static str>,
+    select : Select,
+    script : Script,
+}
+
+//Start, then the button of the lamp Simon shows first
+const SIMON : Game = Game {
+    version: ChipVariant::TMS1000, rom: "simon.bin", ipla: "tests/fixtures/tms1000_mpla.pla", opla: None, select: Select::R,
+    script: &[(10000, &[]), (3000, &[(2, 1)]), (40000, &[]), (3000, &[(1, 4)]), (60000, &[])],
+};
+
+const MP3300 : Game = Game {
+    version: ChipVariant::TMS1000, rom: "mp3300.bin", ipla: "tests/fixtures/tms1000_mpla.pla", opla: None, select: Select::R,
+    script: &[(10000, &[]), (3000, &[(2, 1)]), (40000, &[]), (3000, &[(1, 2)]), (60000, &[])],
+};
+
+const MERLIN : Game = Game {
+    version: ChipVariant::TMS1100, rom: "mp3404.bin", ipla: "ancillary/tms1100_merlin_mpla.pla", opla: Some("ancillary/tms1100_merlin_opla.pla"), select: Select::O,
+    script: &[(20000, &[]), (3000, &[(7, 4)]), (40000, &[]), (3000, &[(6, 2)]), (40000, &[]), (3000, &[(7, 2)]), (60000, &[])],
+};
+
+const SFAIR : Game = Game {
+    version: ChipVariant::TMS1100, rom: "mp1312a-sfair.bin", ipla: "ancillary/tms1100_common1_micro.pla", opla: None, select: Select::R,
+    script: &[(30000, &[]), (5000, &[(0, 2)]), (30000, &[]), (5000, &[(1, 4)]), (30000, &[]), (5000, &[(3, 8)]), (30000, &[])],
+};
+
+fn record(game : &Game, script : &[(u64, &[Key])]) -> String {
+    let opla = game.opla.unwrap_or("ancillary/dummypla.pla");
+    let mut system = SYSTEM::load_system_with_policy(game.version, format!("ancillary/{}", game.rom), game.ipla.to_string(), opla.to_string(), PowerOnPolicy::Zero).unwrap();
+    system.set_logging();
+    let mut results = String::new();
+    let mut last: Option<(u32, u32)> = None;
+    let mut cycle: u64 = 0;
+    for &(cycles, keys) in script.iter() {
+        for _ in 0..cycles {
+            let selected = match game.select {
+                Select::R => system.get_r_outputs_u32(),
+                Select::O => system.get_o_outputs(),
+            };
+            let k = keys.iter().filter(|&&(line, _)| (selected >> line) & 1 == 1).fold(0, |k, &(_, bit)| k | bit);
+            system.instruction_cycle_mut(k);
+            let o = match game.opla {
+                Some(_) => system.get_o_outputs(),
+                None => system.get_o_register(),
+            };
+            let outputs = (system.get_r_outputs_u32(), o);
+            if last != Some(outputs) {
+                results.push_str(&format!("{} {:x} {:x}\n", cycle, outputs.0, outputs.1));
+                last = Some(outputs);
+            }
+            cycle += 1;
+        }
+    }
+    return results;
+}
+
+fn check(name : &str, game : &Game) {
+    let actual = record(game, game.script);
+    //A script whose presses go unread would only record the attract loop
+    let length: u64 = game.script.iter().map(|&(cycles, _)| cycles).sum();
+    assert_ne!(actual, record(game, &[(length, &[])]), "{}: the script's key presses change nothing", name);
+
+    let filename = format!("tests/golden/{}.txt", name);
+    if std::env::var("UPDATE_GOLDEN").as_deref() == Ok("1") {
+        fs::write(&filename, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&filename).unwrap_or_else(|_| panic!("No golden file {}; run with UPDATE_GOLDEN=1 to create it", filename));
+    for (idx, (a, e)) in actual.lines().zip(expected.lines()).enumerate() {
+        assert_eq!(a, e, "{}: line {} differs from {}", name, idx + 1, filename);
+    }
+    assert_eq!(actual.lines().count(), expected.lines().count(), "{}: number of output changes differs from {}", name, filename);
+}
+
+#[test]
+fn simon() {
+    check("simon", &SIMON);
+}
+
+#[test]
+fn mp3300() {
+    check("mp3300", &MP3300);
+}
+
+#[test]
+fn merlin() {
+    check("mp3404", &MERLIN);
+}
+
+#[test]
+fn mp1312a_sfair() {
+    check("mp1312a-sfair", &SFAIR);
+}
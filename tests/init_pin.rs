@@ -0,0 +1,82 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+
+use tms::TMS1000;
+use tms::TMS1000::PowerOnPolicy;
+
+mod common;
+
+//After power-on or an INIT release, the first instruction cycle fetches chapter 0, page 15, PC 0
+fn assert_at_reset_vector(system : &mut TMS1000::SYSTEM) {
+    assert_eq!(system.get_ca_reg(), 0);
+    assert_eq!(system.get_pa_reg(), 15);
+    assert_eq!(system.get_pb_reg(), 15);
+    assert_eq!(system.get_pc_reg(), 0);
+    assert_eq!(system.get_rom_index(), 15 * 64);
+    assert_eq!(system.get_cl_reg(), 0);
+    assert_eq!(system.get_status(), 1);
+}
+
+#[test]
+fn power_on_starts_at_page_15_pc_0() {
+    let mut system = common::merlin(PowerOnPolicy::Sentinel);
+    system.instruction_cycle_mut(0);
+    assert_at_reset_vector(&mut system);
+}
+
+#[test]
+fn init_pin_holds_then_restarts_at_page_15_pc_0() {
+    let mut system = common::merlin(PowerOnPolicy::Sentinel);
+    for _ in 0..500 {
+        system.instruction_cycle_mut(0);
+    }
+    system.set_init(3);
+    for _ in 0..3 {
+        assert!(system.get_init());
+        system.instruction_cycle_mut(0);
+        assert_eq!(system.get_pa_reg(), 15);
+        assert_eq!(system.get_r_outputs_u32(), 0);
+    }
+    assert!(!system.get_init());
+    system.instruction_cycle_mut(0);
+    assert_at_reset_vector(&mut system);
+}
+
+#[test]
+fn init_release_matches_power_on() {
+    let mut fresh = common::merlin(PowerOnPolicy::Sentinel);
+    let mut reset = common::merlin(PowerOnPolicy::Sentinel);
+    for _ in 0..500 {
+        reset.instruction_cycle_mut(0);
+    }
+    reset.set_init(1);
+    reset.instruction_cycle_mut(0);
+    for _ in 0..20 {
+        fresh.instruction_cycle_mut(0);
+        reset.instruction_cycle_mut(0);
+        assert_eq!(fresh.get_rom_index(), reset.get_rom_index());
+        assert_eq!(fresh.get_status(), reset.get_status());
+    }
+}
+
+#[test]
+fn single_steps_count_towards_init_hold() {
+    let mut system = common::merlin(PowerOnPolicy::Sentinel);
+    system.set_init(1);
+    for _ in 0..4 {
+        system.STEP_mut(0);
+    }
+    assert!(!system.get_init());
+    system.instruction_cycle_mut(0);
+    assert_at_reset_vector(&mut system);
+}
+
+#[test]
+fn long_holds_do_not_overflow() {
+    let mut system = common::merlin(PowerOnPolicy::Sentinel);
+    system.set_init(u32::MAX);
+    for _ in 0..100 {
+        system.instruction_cycle_mut(0);
+        assert!(system.get_init());
+    }
+}
@@ -0,0 +1,65 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Listing and symbol files
+
+use proptest::prelude::*;
+
+use tms::assembler;
+use tms::listing::{self, ListingLine, Symbol};
+use tms::TMS1000::{exec_index, format_address, PC_SEQ};
+use tms::variant::ChipVariant;
+
+#[test]
+fn listing_format() {
+    let source = "start: TCY 3\n  YNEC 3\n.org 1,2\n  .byte 0xAB\n";
+    let assembled = assembler::assemble_with_listing(source.to_string(), ChipVariant::TMS1100).unwrap();
+    assert_eq!(listing::format_listing(&assembled.listing), "\
+<input>:1                0 15 00 (00) : 4C  start: TCY 3
+<input>:2                0 15 01 (01) : 5C  YNEC 3
+<input>:4                1 02 00 (00) : AB  .byte 0xAB
+");
+    let line = ListingLine { file: "long/path/to/a/source/file.asm".to_string(), line: 1234, source: "TAY".to_string(), chapter: 0, page: 3, pc: 0x2F, index: 11, byte: 0x24 };
+    assert_eq!(listing::format_listing(&[line]), "long/path/to/a/source/file.asm:1234 0 03 47 (11) : 24  TAY\n");
+}
+
+#[test]
+fn symbol_format() {
+    let source = "start: TCY 3\nloop: YNEC 3\n  BR loop\n.org 1,2\nfar: TAY\n";
+    let assembled = assembler::assemble_with_listing(source.to_string(), ChipVariant::TMS1100).unwrap();
+    assert_eq!(listing::format_symbols(&assembled.symbols), "start 0 15 00 (00)\nloop 0 15 01 (01)\nfar 1 02 00 (00)\n");
+}
+
+#[test]
+fn symbols_roundtrip() {
+    let symbols = vec![
+        Symbol { name: "main".to_string(), chapter: 0, page: 15, pc: 0 },
+        Symbol { name: "far_away".to_string(), chapter: 1, page: 0, pc: 63 },
+        Symbol { name: "_x9".to_string(), chapter: 0, page: 7, pc: PC_SEQ[40] },
+    ];
+    assert_eq!(listing::parse_symbols(&listing::format_symbols(&symbols)), Ok(symbols));
+}
+
+#[test]
+fn symbol_errors() {
+    assert_eq!(listing::parse_symbols("main 0 15 00\n9lives 0 0 0\n"), Err("Symbols: line 2: could not parse \"9lives 0 0 0\"".to_string()));
+    assert_eq!(listing::parse_symbols("\nmain 2 0 0\n"), Err("Symbols: line 2: address 2 0 0 out of range".to_string()));
+    assert_eq!(listing::parse_symbols("main 0 0 64\n"), Err("Symbols: line 1: address 0 0 64 out of range".to_string()));
+    assert!(listing::read_symbols("tests/fixtures/no such file").is_err());
+}
+
+#[test]
+fn helpers() {
+    for (index, &pc) in PC_SEQ.iter().enumerate() {
+        assert_eq!(exec_index(pc), index);
+    }
+    assert_eq!(format_address(1024 + (64 * 15) + 7), "1 15 07");
+    assert_eq!(format_address(64 * 3), "0 03 00");
+}
+
+proptest! {
+    #[test]
+    fn any_symbols_roundtrip(entries in proptest::collection::vec(("[A-Za-z_][A-Za-z0-9_]{0,12}", 0usize..2, 0usize..16, 0usize..64), 0..20)) {
+        let symbols: Vec<Symbol> = entries.into_iter().map(|(name, chapter, page, pc)| Symbol { name, chapter, page, pc }).collect();
+        prop_assert_eq!(listing::parse_symbols(&listing::format_symbols(&symbols)), Ok(symbols));
+    }
+}
@@ -0,0 +1,25 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Merlin (TMS1100) against a trace captured from MAME with the tracelog command in README.txt
+//No capture is checked in yet, so the test is ignored. Save the log as tests/fixtures/mame/merlin.log
+//and run
+//    cargo test --test mame_capture -- --ignored
+
+use tms::mame;
+use tms::TMS1000::PowerOnPolicy;
+
+mod common;
+
+const CAPTURE : &str = "tests/fixtures/mame/merlin.log";
+
+#[test]
+#[ignore = "needs a MAME capture in tests/fixtures/mame/merlin.log"]
+fn merlin_matches_mame() {
+    let records = mame::read_mame_trace(CAPTURE).unwrap();
+    assert!(!records.is_empty(), "{} holds no instructions", CAPTURE);
+    //MAME powers up with RAM and registers cleared
+    match mame::check(&mut common::merlin(PowerOnPolicy::Zero), &records, 0) {
+        Ok(checked) => assert_eq!(checked, records.len()),
+        Err(mismatch) => panic!("{}: {}", CAPTURE, mismatch),
+    }
+}
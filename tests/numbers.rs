@@ -0,0 +1,74 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Number syntax shared by the compiler and assembler
+
+use tms::assembler;
+use tms::compiler::{self, parse_number};
+use tms::variant::ChipVariant;
+
+#[test]
+fn decimal() {
+    assert_eq!(parse_number("0"), Some(0));
+    assert_eq!(parse_number("42"), Some(42));
+    assert_eq!(parse_number(" 007 "), Some(7));
+}
+
+#[test]
+fn hexadecimal() {
+    assert_eq!(parse_number("0x1F"), Some(31));
+    assert_eq!(parse_number("0X1f"), Some(31));
+    assert_eq!(parse_number("$FF"), Some(255));
+    assert_eq!(parse_number("$0"), Some(0));
+}
+
+#[test]
+fn binary() {
+    assert_eq!(parse_number("0b1010"), Some(10));
+    assert_eq!(parse_number("0B1"), Some(1));
+    assert_eq!(parse_number("%1111"), Some(15));
+}
+
+#[test]
+fn characters() {
+    assert_eq!(parse_number("'A'"), Some(65));
+    assert_eq!(parse_number("'0'"), Some(48));
+    assert_eq!(parse_number("';'"), Some(59));
+    assert_eq!(parse_number("' '"), Some(32));
+}
+
+#[test]
+fn bad_forms() {
+    for token in ["", "0x", "$", "0b", "%", "''", "'AB'", "'A", "0x1G", "0b102", "%2", "-1", "+1", "0x+1", "$-1", "%+1", "1.5", "ten"] {
+        assert_eq!(parse_number(token), None, "{:?}", token);
+    }
+}
+
+#[test]
+fn overflow() {
+    assert_eq!(parse_number(&usize::MAX.to_string()), Some(usize::MAX));
+    assert_eq!(parse_number("99999999999999999999999"), None);
+    assert_eq!(parse_number("0x1FFFFFFFFFFFFFFFF"), None);
+    assert_eq!(parse_number(&format!("%1{}", "0".repeat(64))), None);
+}
+
+#[test]
+fn operands_are_range_checked_after_parsing() {
+    let diagnostics = assembler::assemble("  TCY 'A'\n".to_string(), ChipVariant::TMS1000).unwrap_err();
+    assert_eq!(diagnostics[0].to_string(), "<input>:1:3: Operand 65 of TCY out of range (0-15)");
+    let diagnostics = compiler::compile("0 15 00 : TCY 'A'\n".to_string(), ChipVariant::TMS1000).unwrap_err();
+    assert_eq!(diagnostics[0].to_string(), "<input>:1:15: Operand 65 of TCY out of range (0-15)");
+    let rom = assembler::assemble("  TCY %1010\n  LDX $3\n".to_string(), ChipVariant::TMS1000).unwrap();
+    assert_eq!(rom[64 * 15], compiler::compile_instruction("TCY", 10, ChipVariant::TMS1000).unwrap());
+    assert_eq!(rom[(64 * 15) + 1], compiler::compile_instruction("LDX", 3, ChipVariant::TMS1000).unwrap());
+}
+
+#[test]
+fn bad_numbers_are_reported() {
+    let diagnostics = assembler::assemble("  TCY 0x\n".to_string(), ChipVariant::TMS1000).unwrap_err();
+    assert_eq!(diagnostics[0].to_string(), "<input>:1:3: Invalid number 0x");
+    let diagnostics = assembler::assemble(".fill 0x\n".to_string(), ChipVariant::TMS1000).unwrap_err();
+    assert_eq!(diagnostics[0].to_string(), "<input>:1:1: Invalid number 0x");
+    let diagnostics = assembler::assemble("  YNEC %12
+".to_string(), ChipVariant::TMS1000).unwrap_err();
+    assert_eq!(diagnostics[0].to_string(), "<input>:1:3: Invalid number %12");
+}
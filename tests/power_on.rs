@@ -0,0 +1,84 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Power-on policies for RAM, the accumulator, X, Y and the status latch
+
+use std::collections::HashMap;
+
+use tms::TMS1000::{PowerOnPolicy, SYSTEM};
+use tms::variant::ChipVariant;
+
+mod common;
+
+//RAM, X, Y, A and SL
+type State = ([[u8; 16]; 8], usize, usize, u8, u8);
+
+fn power_on(version : ChipVariant, policy : PowerOnPolicy) -> State {
+    let mut system = SYSTEM::from_tables(version, vec![0; version.rom_size()], HashMap::new(), HashMap::new(), policy).unwrap();
+    return (system.get_ram_array(), system.get_x_reg(), system.get_y_reg(), system.get_acc_reg(), system.get_sl_reg());
+}
+
+#[test]
+fn policy_names() {
+    assert_eq!("zero".parse::<PowerOnPolicy>(), Ok(PowerOnPolicy::Zero));
+    assert_eq!(" Sentinel\n".parse::<PowerOnPolicy>(), Ok(PowerOnPolicy::Sentinel));
+    assert_eq!("random:42".parse::<PowerOnPolicy>(), Ok(PowerOnPolicy::Random(42)));
+    assert_eq!("RANDOM:18446744073709551615".parse::<PowerOnPolicy>(), Ok(PowerOnPolicy::Random(u64::MAX)));
+    for bad in ["", "random", "random:", "random:-1", "random:x", "garbage"] {
+        assert!(bad.parse::<PowerOnPolicy>().is_err(), "{:?} parsed", bad);
+    }
+}
+
+#[test]
+fn zero_clears_everything() {
+    assert_eq!(power_on(ChipVariant::TMS1100, PowerOnPolicy::Zero), ([[0; 16]; 8], 0, 0, 0, 0));
+}
+
+#[test]
+fn sentinel_fills_everything_with_255() {
+    assert_eq!(power_on(ChipVariant::TMS1000, PowerOnPolicy::Sentinel), ([[255; 16]; 8], 255, 255, 255, 255));
+}
+
+#[test]
+fn sentinel_is_the_default() {
+    let mut system = SYSTEM::load_system(ChipVariant::TMS1100, "ancillary/mp3404.bin".to_string(), "ancillary/tms1100_merlin_mpla.pla".to_string(), "ancillary/tms1100_merlin_opla.pla".to_string()).unwrap();
+    assert_eq!(system.get_ram_array(), [[255; 16]; 8]);
+    assert_eq!(system.get_acc_reg(), 255);
+}
+
+#[test]
+fn random_is_reproducible_from_the_seed() {
+    for version in [ChipVariant::TMS1000, ChipVariant::TMS1100] {
+        assert_eq!(power_on(version, PowerOnPolicy::Random(1)), power_on(version, PowerOnPolicy::Random(1)));
+        assert_ne!(power_on(version, PowerOnPolicy::Random(1)).0, power_on(version, PowerOnPolicy::Random(2)).0);
+    }
+    let from_file = |seed : u64| {
+        let mut system = common::merlin(PowerOnPolicy::Random(seed));
+        return (system.get_ram_array(), system.get_x_reg(), system.get_y_reg(), system.get_acc_reg(), system.get_sl_reg());
+    };
+    assert_eq!(from_file(7), power_on(ChipVariant::TMS1100, PowerOnPolicy::Random(7)));
+}
+
+#[test]
+fn random_values_are_in_range() {
+    for version in [ChipVariant::TMS1000, ChipVariant::TMS1100] {
+        for seed in 0..64 {
+            let (ram, x, y, a, sl) = power_on(version, PowerOnPolicy::Random(seed));
+            assert!(ram.iter().flatten().all(|&word| word < 16));
+            assert!(x < version.ram_files(), "{}: X = {}", version, x);
+            assert!(y < 16);
+            assert!(a < 16);
+            assert!(sl < 2);
+        }
+    }
+}
+
+#[test]
+fn random_x_covers_every_file() {
+    for version in [ChipVariant::TMS1000, ChipVariant::TMS1100] {
+        let mut seen = vec![false; version.ram_files()];
+        for seed in 0..256 {
+            seen[power_on(version, PowerOnPolicy::Random(seed)).1] = true;
+        }
+        assert!(seen.iter().all(|&s| s), "{}: {:?}", version, seen);
+    }
+}
@@ -0,0 +1,87 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Profile of Merlin (TMS1100), and of a small assembled program with known subroutines
+
+use std::collections::HashMap;
+use std::fs;
+
+use tms::assembler;
+use tms::listing;
+use tms::profiler::Profile;
+use tms::TMS1000::PowerOnPolicy;
+use tms::variant::ChipVariant;
+
+mod common;
+
+#[test]
+fn every_cycle_is_charged_once() {
+    let mut system = common::merlin(PowerOnPolicy::Sentinel);
+    system.set_profiling(true);
+    common::run(&mut system, 5000, 0);
+    let profile = system.get_profile().unwrap();
+    assert_eq!(profile.total_cycles(), 5000);
+    assert_eq!(profile.top_level_cycles + profile.subroutine_cycles.values().sum::<u64>(), 5000);
+    assert!(!profile.subroutine_cycles.is_empty(), "Merlin calls subroutines straight after reset");
+
+    let folded = profile.folded(&HashMap::new());
+    let folded_total: u64 = folded.lines().map(|l| l.rsplit(' ').next().unwrap().parse::<u64>().unwrap()).sum();
+    assert_eq!(folded_total, 5000);
+}
+
+//outer goes on to inner with a CALL, which inside a subroutine only branches, so inner's RETN returns
+//from outer and all five cycles are outer's. That CALL also copies the page address to the page
+//buffer, so inner loads the caller's page before returning.
+const NESTED : &str = "  CALL outer\n  CALL other\nend: BR end\n.org 0,3\nouter: TCY 1\n  CALL inner\ninner: TCY 2\n  LDP 15\n  RETN\n.org 0,4\nother: RETN\n";
+
+//Runs NESTED for 20 cycles, returning the profile and the symbols as read back from a symbol file
+//`test` names the symbol file, since the tests run in parallel
+fn nested(test : &str) -> (Profile, HashMap<usize, String>) {
+    let assembled = assembler::assemble_with_listing(NESTED.to_string(), ChipVariant::TMS1000).unwrap();
+    let filename = std::env::temp_dir().join(format!("tms-profiler-{}-{}.sym", std::process::id(), test));
+    fs::write(&filename, listing::format_symbols(&assembled.symbols)).unwrap();
+    let symbols = listing::read_symbols(filename.to_str().unwrap()).unwrap();
+    fs::remove_file(&filename).unwrap();
+
+    let mut system = common::load(ChipVariant::TMS1000, NESTED, PowerOnPolicy::Zero);
+    system.set_profiling(true);
+    common::run(&mut system, 20, 0);
+    return (system.get_profile().unwrap(), symbols);
+}
+
+fn entry(symbols : &HashMap<usize, String>, name : &str) -> usize {
+    return *symbols.iter().find(|(_, v)| v.as_str() == name).unwrap().0;
+}
+
+#[test]
+fn nested_calls_are_charged_to_the_entry_point() {
+    let (profile, symbols) = nested("nested");
+    let charged: Vec<(usize, u64)> = profile.subroutine_cycles.iter().map(|(&k, &v)| (k, v)).collect();
+    assert_eq!(charged, [(entry(&symbols, "outer"), 5), (entry(&symbols, "other"), 1)]);
+    assert_eq!(profile.top_level_cycles, 14);
+}
+
+#[test]
+fn report_is_ordered_busiest_first() {
+    let (profile, symbols) = nested("report");
+    let report = profile.report(&symbols, 10);
+    let (routines, addresses) = report.split_once("\nBy address\n").unwrap();
+    let routines: Vec<(u64, &str)> = routines.lines().skip(3).map(|l| {
+        let fields: Vec<&str> = l.split_whitespace().collect();
+        return (fields[0].parse().unwrap(), fields[2]);
+    }).collect();
+    assert_eq!(routines, [(14, "main"), (5, "outer"), (1, "other")]);
+
+    let counts: Vec<u64> = addresses.lines().map(|l| l.split_whitespace().next().unwrap().parse().unwrap()).collect();
+    assert!(counts.len() > 3);
+    assert!(counts.windows(2).all(|pair| pair[0] >= pair[1]), "{}", addresses);
+    assert!(addresses.lines().next().unwrap().ends_with("  end"), "{}", addresses);
+}
+
+#[test]
+fn folded_stacks_are_named_from_the_symbol_file() {
+    let (profile, symbols) = nested("folded");
+    assert_eq!(profile.folded(&symbols), "main 14\nmain;outer 5\nmain;other 1\n");
+    //Entry points without a symbol are named by address
+    let unnamed = format!("main;S_0_03_{:0>2} 5", entry(&symbols, "outer") % 64);
+    assert_eq!(profile.folded(&HashMap::new()).lines().nth(1), Some(unnamed.as_str()));
+}
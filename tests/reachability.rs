@@ -0,0 +1,68 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Reachability reports on the programs in tests/fixtures/reachability, one per kind of finding
+
+use tms::assembler;
+use tms::reachability::{self, FreeRun, ReachabilityReport};
+use tms::TMS1000::PC_SEQ;
+use tms::variant::ChipVariant;
+
+fn report(name : &str) -> (Vec<u8>, ReachabilityReport) {
+    let assembled = assembler::assemble_file(format!("tests/fixtures/reachability/{}.asm", name), ChipVariant::TMS1000).unwrap();
+    let report = reachability::report(&assembled.rom, ChipVariant::TMS1000);
+    return (assembled.rom, report);
+}
+
+//Free runs other than whole unused pages
+fn partial_runs(report : &ReachabilityReport) -> Vec<FreeRun> {
+    return report.free.iter().filter(|r| r.bytes() < 64).cloned().collect();
+}
+
+#[test]
+fn clean() {
+    let (rom, report) = report("clean");
+    assert_eq!(report.reachable_count(), 8);
+    assert!(report.mid_routine.is_empty());
+    assert!(report.cross_page.is_empty());
+    let text = report.format(&rom, ChipVariant::TMS1000);
+    assert!(text.contains("Subroutine entries in the middle of a routine (0)\n"), "{}", text);
+    assert!(text.contains("Cross-page branches without an LDP in their block (0)\n"), "{}", text);
+}
+
+#[test]
+fn free_runs() {
+    let (rom, report) = report("free");
+    assert_eq!((report.size, report.reachable_count()), (1024, 3));
+    assert_eq!(partial_runs(&report), [
+        FreeRun { chapter: 0, page: 15, first: 1, last: 2 },
+        FreeRun { chapter: 0, page: 15, first: 5, last: 63 },
+    ]);
+    //Every other page is unused
+    assert_eq!(report.free.len(), 15 + 2);
+    //Largest first
+    let text = report.format(&rom, ChipVariant::TMS1000);
+    let first = text.find("index 05-63, 59 bytes (PC 31 to 32)").unwrap();
+    let second = text.find("index 01-02, 2 bytes (PC 01 to 03)").unwrap();
+    assert!(first < second, "{}", text);
+}
+
+#[test]
+fn subroutine_entered_by_falling_through() {
+    let (rom, report) = report("mid_routine");
+    assert_eq!(report.mid_routine.len(), 1);
+    let finding = &report.mid_routine[0];
+    assert_eq!((finding.from, finding.to), ((64 * 15) + PC_SEQ[1], (64 * 2) + PC_SEQ[1]));
+    assert!(report.cross_page.is_empty());
+    let text = report.format(&rom, ChipVariant::TMS1000);
+    assert!(text.contains("Subroutine entries in the middle of a routine (1)\n  0 15 01 : CALL 1 (1) -> 0 02 01: also reached by falling through from 0 02 00\n"), "{}", text);
+}
+
+#[test]
+fn page_change_without_an_LDP() {
+    let (rom, report) = report("cross_page");
+    assert!(report.mid_routine.is_empty());
+    assert_eq!(report.cross_page.len(), 1);
+    assert_eq!((report.cross_page[0].from, report.cross_page[0].to), ((64 * 15) + PC_SEQ[2], 64 * 3));
+    let text = report.format(&rom, ChipVariant::TMS1000);
+    assert!(text.contains("Cross-page branches without an LDP in their block (1)\n  0 15 03 : BR 0 (0) -> 0 03 00: page buffer loaded before the start of the block\n"), "{}", text);
+}
@@ -0,0 +1,98 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//ROM sizes, and the fitting of images that do not match them
+
+use std::collections::HashMap;
+
+use tms::TMS1000::{AccessPolicy, PowerOnPolicy, SYSTEM};
+use tms::variant::ChipVariant;
+
+const RESET_VECTOR : usize = 64 * 15; //chapter 0, page 15, PC 0
+
+//Loads an image, and fetches the instruction at the reset vector
+fn boot(version : ChipVariant, rom : Vec<u8>) -> SYSTEM {
+    let mut system = SYSTEM::from_tables(version, rom, HashMap::new(), HashMap::new(), PowerOnPolicy::Zero).unwrap();
+    system.set_logging();
+    system.set_access_policy(AccessPolicy::Halt);
+    system.instruction_cycle_mut(0);
+    return system;
+}
+
+#[test]
+fn sizes() {
+    for version in [ChipVariant::TMS1000, ChipVariant::TMS1070, ChipVariant::TMS1200, ChipVariant::TMS1270] {
+        assert_eq!(version.rom_size(), 1024, "{}", version);
+    }
+    for version in [ChipVariant::TMS1100, ChipVariant::TMS1300] {
+        assert_eq!(version.rom_size(), 2048, "{}", version);
+    }
+}
+
+#[test]
+fn exact_images_are_untouched() {
+    let rom: Vec<u8> = (0..2048).map(|i| i as u8).collect();
+    assert_eq!(ChipVariant::TMS1100.fit_rom(&rom), (rom, None));
+}
+
+#[test]
+fn short_images_are_mirrored() {
+    let rom: Vec<u8> = (0..512).map(|i| (i % 251) as u8).collect();
+    let (fitted, warning) = ChipVariant::TMS1000.fit_rom(&rom);
+    assert_eq!(fitted.len(), 1024);
+    assert_eq!(&fitted[..512], &rom[..]);
+    assert_eq!(&fitted[512..], &rom[..]);
+    assert!(warning.unwrap().contains("mirrored 2 times"));
+}
+
+#[test]
+fn odd_sized_images_are_padded() {
+    let rom = vec![0x42; 1000];
+    let (fitted, warning) = ChipVariant::TMS1200.fit_rom(&rom);
+    assert_eq!(fitted.len(), 1024);
+    assert_eq!(&fitted[..1000], &rom[..]);
+    assert!(fitted[1000..].iter().all(|&b| b == 0));
+    assert!(warning.unwrap().contains("padded with 0"));
+
+    let (fitted, warning) = ChipVariant::TMS1000.fit_rom(&[]);
+    assert_eq!(fitted, vec![0; 1024]);
+    assert!(warning.is_some());
+}
+
+#[test]
+fn long_images_are_cut() {
+    let rom = vec![7; 2048];
+    let (fitted, warning) = ChipVariant::TMS1000.fit_rom(&rom);
+    assert_eq!(fitted, vec![7; 1024]);
+    assert!(warning.unwrap().contains("the last 1024 bytes are ignored"));
+}
+
+#[test]
+fn the_core_runs_fitted_images() {
+    //512 bytes: the reset vector lands in the mirror of page 7
+    let mut rom = vec![0; 512];
+    rom[RESET_VECTOR % 512] = 0x42;
+    let mut system = boot(ChipVariant::TMS1000, rom);
+    assert_eq!(system.get_instruction(), 0x42);
+    assert!(!system.is_halted());
+    assert!(system.get_rom_warning().is_some());
+
+    //A 1K image on a TMS1100 repeats in chapter 1
+    let mut rom = vec![0; 1024];
+    rom[RESET_VECTOR] = 0x42;
+    let mut system = boot(ChipVariant::TMS1100, rom);
+    assert_eq!(system.get_instruction(), 0x42);
+    assert!(system.get_rom_warning().unwrap().contains("TMS1100"));
+
+    //Too short to reach the reset vector, and not a divisor of the ROM size
+    let mut system = boot(ChipVariant::TMS1000, vec![0x42; 900]);
+    assert_eq!(system.get_instruction(), 0);
+    assert!(!system.is_halted());
+}
+
+#[test]
+fn the_bundled_roms_fit() {
+    for (version, rom) in [(ChipVariant::TMS1000, "simon.bin"), (ChipVariant::TMS1000, "mp3300.bin"), (ChipVariant::TMS1100, "mp3404.bin"), (ChipVariant::TMS1100, "mp1312a-sfair.bin")] {
+        let system = SYSTEM::load_system(version, format!("ancillary/{}", rom), "tests/fixtures/tms1000_mpla.pla".to_string(), "ancillary/dummypla.pla".to_string()).unwrap();
+        assert_eq!(system.get_rom_warning(), None, "{}", rom);
+    }
+}
@@ -0,0 +1,76 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//decompile -> compile must give back the original ROM, byte for byte
+
+use proptest::prelude::*;
+
+use tms::compiler;
+use tms::decompiler;
+use tms::disassembler;
+use tms::variant::ChipVariant;
+
+fn roundtrip(rom : &[u8], version : ChipVariant) -> Vec<u8> {
+    let lines = decompiler::decompile_rom(rom, version);
+    let source: String = lines.iter().map(|l| format!("{}\n", l)).collect();
+    return compiler::compile(source, version).expect("decompiler output should compile");
+}
+
+//The same, through the "idx - " lines printed by the decompile binary
+fn roundtrip_display(rom : &[u8], version : ChipVariant) -> Vec<u8> {
+    let lines = decompiler::decompile_rom(rom, version);
+    let source: String = lines.iter().enumerate().map(|(idx, l)| format!("{} - {}\n", idx % 64, l)).collect();
+    return compiler::compile(source, version).expect("display output should compile");
+}
+
+fn bundled(name : &str) -> Vec<u8> {
+    return std::fs::read(format!("ancillary/{}", name)).unwrap();
+}
+
+#[test]
+fn bundled_TMS1000_roms_roundtrip() {
+    for name in ["simon.bin", "mp3300.bin"] {
+        let rom = bundled(name);
+        assert_eq!(roundtrip(&rom, ChipVariant::TMS1000), rom, "{}", name);
+        assert_eq!(roundtrip_display(&rom, ChipVariant::TMS1000), rom, "{}", name);
+    }
+}
+
+#[test]
+fn bundled_TMS1100_roms_roundtrip() {
+    for name in ["mp3404.bin", "mp1312a-sfair.bin"] {
+        let rom = bundled(name);
+        assert_eq!(roundtrip(&rom, ChipVariant::TMS1100), rom, "{}", name);
+        assert_eq!(roundtrip_display(&rom, ChipVariant::TMS1100), rom, "{}", name);
+    }
+}
+
+#[test]
+fn bundled_roms_reassemble_from_disassembly() {
+    for (name, version) in [("simon.bin", ChipVariant::TMS1000), ("mp3300.bin", ChipVariant::TMS1000), ("mp3404.bin", ChipVariant::TMS1100), ("mp1312a-sfair.bin", ChipVariant::TMS1100)] {
+        let rom = bundled(name);
+        let source = disassembler::disassemble(&rom, version);
+        assert_eq!(compiler::compile(source, version).expect("disassembly should compile"), rom, "{}", name);
+    }
+}
+
+#[test]
+fn wrong_execution_index_is_reported() {
+    let diagnostics = compiler::compile("0 15 01 (02) : TCY 3\n".to_string(), ChipVariant::TMS1000).unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, 1);
+}
+
+proptest! {
+    //Each case compiles a whole ROM, so fewer than the default 256
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn any_TMS1000_rom_roundtrips(rom in proptest::collection::vec(any::<u8>(), 1024)) {
+        prop_assert_eq!(roundtrip(&rom, ChipVariant::TMS1000), rom);
+    }
+
+    #[test]
+    fn any_TMS1100_rom_roundtrips(rom in proptest::collection::vec(any::<u8>(), 2048)) {
+        prop_assert_eq!(roundtrip(&rom, ChipVariant::TMS1100), rom);
+    }
+}
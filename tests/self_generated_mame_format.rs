@@ -0,0 +1,47 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//MAME trace harness, against a Merlin trace written by this emulator in MAME's format
+//The fixture checks the parser and the comparison, not the core: no trace captured from MAME is checked in
+
+use tms::mame::{self, Mismatch};
+use tms::TMS1000::PowerOnPolicy;
+
+mod common;
+
+const FIXTURE : &str = "tests/fixtures/self_generated_merlin.log";
+
+#[test]
+fn self_generated_fixture_matches() {
+    let records = mame::read_mame_trace(FIXTURE).unwrap();
+    assert_eq!(records.len(), 150);
+    assert_eq!(records[0].rom_address, 0x3C0);
+    assert_eq!(records[0].registers.get("PA"), Some(&15));
+    assert_eq!(mame::check(&mut common::merlin(PowerOnPolicy::Zero), &records, 0), Ok(150));
+}
+
+#[test]
+fn reports_first_mismatch() {
+    let mut records = mame::read_mame_trace(FIXTURE).unwrap();
+    *records[40].registers.get_mut("Y").unwrap() ^= 1;
+    records[60].rom_address ^= 1;
+    let mismatch = mame::check(&mut common::merlin(PowerOnPolicy::Zero), &records, 0).unwrap_err();
+    assert_eq!((mismatch.index, mismatch.field.as_str()), (40, "Y"));
+    assert_eq!(mismatch.line, 47);
+
+    *records[40].registers.get_mut("Y").unwrap() ^= 1;
+    records.truncate(45);
+    records[42].rom_address += 1;
+    let Mismatch { index, field, .. } = mame::check(&mut common::merlin(PowerOnPolicy::Zero), &records, 0).unwrap_err();
+    assert_eq!((index, field.as_str()), (42, "address"));
+}
+
+#[test]
+fn parse_errors() {
+    assert!(mame::parse_mame_trace("A=0 X=0 3C0: LDX 7\n   (loops for 12 instructions)\n").unwrap_err().contains("noloop"));
+    assert!(mame::parse_mame_trace("A=0 Q=1 3C0: LDX 7\n").unwrap_err().contains("unknown register Q"));
+    assert!(mame::parse_mame_trace("A=0 X=0\n").unwrap_err().contains("no address"));
+    let records = mame::parse_mame_trace("# comment\n\nstatus=1 cs=0 3C0: LDX 7\n").unwrap();
+    assert_eq!(records[0].registers.get("STATUS"), Some(&1));
+    assert_eq!(records[0].disassembly, "LDX 7");
+    assert_eq!(records[0].line, 3);
+}
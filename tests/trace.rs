@@ -0,0 +1,97 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Trace files written from Merlin (TMS1100) and read back, and lockstep runs of two systems
+
+use std::fs;
+use std::process::Command;
+
+use tms::TMS1000::PowerOnPolicy;
+use tms::trace::{self, TraceRecord, TraceWriter};
+use tms::variant::ChipVariant;
+
+mod common;
+
+fn record(cycles : u64) -> Vec<TraceRecord> {
+    let mut system = common::merlin(PowerOnPolicy::Sentinel);
+    return (0..cycles).map(|cycle| TraceRecord::step(&mut system, ChipVariant::TMS1100, cycle, 0)).collect();
+}
+
+#[test]
+fn write_then_read() {
+    let records = record(300);
+    let mut writer = TraceWriter::new(Vec::new()).unwrap();
+    for r in records.iter() {
+        writer.write(r).unwrap();
+    }
+    let text = String::from_utf8(writer.into_inner()).unwrap();
+    assert!(text.starts_with(trace::HEADER));
+    assert_eq!(trace::parse_trace(&text).unwrap(), records);
+}
+
+#[test]
+fn first_divergence() {
+    let left = record(300);
+    assert_eq!(trace::first_divergence(&left, &left), None);
+
+    let mut right = left.clone();
+    right[120].accumulator ^= 1;
+    right[200].y ^= 1;
+    let d = trace::first_divergence(&left, &right).unwrap();
+    assert_eq!(d.index, 120);
+    assert_eq!(d.fields, vec!["A"]);
+
+    let d = trace::first_divergence(&left, &left[..250]).unwrap();
+    assert_eq!(d.index, 250);
+    assert_eq!(d.right, None);
+}
+
+#[test]
+fn lockstep_identical() {
+    assert_eq!(trace::lockstep(&mut common::merlin(PowerOnPolicy::Zero), &mut common::merlin(PowerOnPolicy::Zero), ChipVariant::TMS1100, 3000, 0), None);
+}
+
+#[test]
+fn lockstep_finds_ram() {
+    let d = trace::lockstep(&mut common::merlin(PowerOnPolicy::Zero), &mut common::merlin(PowerOnPolicy::Sentinel), ChipVariant::TMS1100, 3000, 0).unwrap();
+    assert_eq!(d.cycle, 0);
+    assert!(d.fields.contains(&"RAM 0,0".to_string()));
+    let table = d.left.side_by_side(&d.right);
+    assert!(table.lines().any(|l| l.starts_with("* RAM 7")));
+    assert!(table.lines().any(|l| l.starts_with("  PA")));
+}
+
+//Two programs that part ways at cycle 2 (cycle 0 runs the no-op loaded at reset)
+const STORE_5 : &str = "  TCY 2|  TCMIY 5|  TCY 4|  SETR|end: BR end";
+const STORE_6 : &str = "  TCY 2|  TCMIY 6|  TCY 4|  SETR|end: BR end";
+
+#[test]
+fn lockstep_finds_the_first_differing_cycle() {
+    let mut left = common::load(ChipVariant::TMS1000, STORE_5, PowerOnPolicy::Zero);
+    let mut right = common::load(ChipVariant::TMS1000, STORE_6, PowerOnPolicy::Zero);
+    let d = trace::lockstep(&mut left, &mut right, ChipVariant::TMS1000, 50, 0).unwrap();
+    assert_eq!(d.cycle, 2);
+    assert_eq!(d.fields, ["opcode", "mnemonic", "RAM 0,2"]);
+    assert_eq!((d.left.ram[0][2], d.right.ram[0][2]), (5, 6));
+    let table = d.left.side_by_side(&d.right);
+    let marked: Vec<&str> = table.lines().filter(|l| l.starts_with('*')).map(|l| l.split_whitespace().nth(1).unwrap()).collect();
+    assert_eq!(marked, ["opcode", "RAM"]);
+}
+
+#[test]
+fn tracediff_reports_the_changed_output_row() {
+    //The second program resets R4 where the first sets it
+    let directory = std::env::temp_dir().join(format!("tms-tracediff-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let (setr, rstr) = (directory.join("setr.bin"), directory.join("rstr.bin"));
+    fs::write(&setr, common::assemble(ChipVariant::TMS1000, STORE_5)).unwrap();
+    fs::write(&rstr, common::assemble(ChipVariant::TMS1000, &STORE_5.replace("SETR", "RSTR"))).unwrap();
+
+    let ipla = common::ipla(ChipVariant::TMS1000);
+    let run = Command::new(env!("CARGO_BIN_EXE_tracediff")).args(["1000", setr.to_str().unwrap(), ipla, common::FIXTURE_OPLA, rstr.to_str().unwrap(), "-", "-", "50", "0", "zero"]).output().unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+    let stdout = String::from_utf8(run.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some("First difference after cycle 4: opcode, R, mnemonic"));
+    let marked: Vec<Vec<&str>> = lines.filter(|l| l.starts_with('*')).map(|l| l.split_whitespace().skip(1).collect()).collect();
+    assert_eq!(marked, [vec!["opcode", "0d", "SETR", "0c", "RSTR"], vec!["R", "10", "0"]]);
+}
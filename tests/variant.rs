@@ -0,0 +1,22 @@
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+//Parsing chip variants from the version argument
+
+use tms::variant::ChipVariant;
+
+#[test]
+fn part_numbers() {
+    for variant in ChipVariant::ALL {
+        assert_eq!(variant.number().to_string().parse(), Ok(variant));
+        assert_eq!(variant.to_string().parse(), Ok(variant));
+    }
+    assert_eq!("tms1100".parse(), Ok(ChipVariant::TMS1100));
+    assert_eq!(" 1300 ".parse(), Ok(ChipVariant::TMS1300));
+}
+
+#[test]
+fn other_spellings_are_rejected() {
+    for input in ["+1100", "01100", "1100.0", "1_100", "TMS+1000", "TMS01000", "1110", "", "TMS", "0980"] {
+        assert_eq!(input.parse::<ChipVariant>(), Err(format!("Unknown chip variant \"{}\" (expected one of 1000, 1070, 1100, 1200, 1270, 1300)", input)), "{:?}", input);
+    }
+}